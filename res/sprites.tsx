@@ -1,4 +1,319 @@
 <?xml version="1.0" encoding="UTF-8"?>
-<tileset version="1.10" tiledversion="1.11.0" name="sprites" tilewidth="16" tileheight="16" tilecount="10" columns="10">
- <image source="Sprites.png" width="160" height="16"/>
+<tileset version="1.10" tiledversion="1.11.0" name="sprites" tilewidth="16" tileheight="16" tilecount="68" columns="68">
+ <image source="Sprites.png" width="1088" height="16"/>
+ <tile id="1">
+  <properties>
+   <property name="animation" value="planks_toggle"/>
+  </properties>
+  <animation>
+   <frame tileid="1" duration="1000"/>
+  </animation>
+ </tile>
+ <tile id="6">
+  <animation>
+   <frame tileid="6" duration="400"/>
+   <frame tileid="56" duration="400"/>
+  </animation>
+ </tile>
+ <tile id="7">
+  <animation>
+   <frame tileid="7" duration="400"/>
+   <frame tileid="57" duration="400"/>
+  </animation>
+ </tile>
+ <tile id="10">
+  <properties>
+   <property name="dir_x" type="int" value="1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="conveyor"/>
+  </properties>
+  <animation>
+   <frame tileid="10" duration="200"/>
+   <frame tileid="52" duration="200"/>
+  </animation>
+ </tile>
+ <tile id="11">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="-1"/>
+   <property name="floor" value="conveyor"/>
+  </properties>
+  <animation>
+   <frame tileid="11" duration="200"/>
+   <frame tileid="53" duration="200"/>
+  </animation>
+ </tile>
+ <tile id="12">
+  <properties>
+   <property name="dir_x" type="int" value="-1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="conveyor"/>
+  </properties>
+  <animation>
+   <frame tileid="12" duration="200"/>
+   <frame tileid="54" duration="200"/>
+  </animation>
+ </tile>
+ <tile id="13">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="1"/>
+   <property name="floor" value="conveyor"/>
+  </properties>
+  <animation>
+   <frame tileid="13" duration="200"/>
+   <frame tileid="55" duration="200"/>
+  </animation>
+ </tile>
+ <tile id="14">
+  <properties>
+   <property name="floor" value="ice"/>
+  </properties>
+ </tile>
+ <tile id="15">
+  <properties>
+   <property name="dir_x" type="int" value="1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="one_way"/>
+  </properties>
+ </tile>
+ <tile id="16">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="-1"/>
+   <property name="floor" value="one_way"/>
+  </properties>
+ </tile>
+ <tile id="17">
+  <properties>
+   <property name="dir_x" type="int" value="-1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="one_way"/>
+  </properties>
+ </tile>
+ <tile id="18">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="1"/>
+   <property name="floor" value="one_way"/>
+  </properties>
+ </tile>
+ <tile id="19">
+  <properties>
+   <property name="dir_x" type="int" value="1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="arrow"/>
+  </properties>
+ </tile>
+ <tile id="20">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="-1"/>
+   <property name="floor" value="arrow"/>
+  </properties>
+ </tile>
+ <tile id="21">
+  <properties>
+   <property name="dir_x" type="int" value="-1"/>
+   <property name="dir_y" type="int" value="0"/>
+   <property name="floor" value="arrow"/>
+  </properties>
+ </tile>
+ <tile id="22">
+  <properties>
+   <property name="dir_x" type="int" value="0"/>
+   <property name="dir_y" type="int" value="1"/>
+   <property name="floor" value="arrow"/>
+  </properties>
+ </tile>
+ <tile id="26">
+  <properties>
+   <property name="animation" value="adventurer_walk_down"/>
+  </properties>
+  <animation>
+   <frame tileid="26" duration="150"/>
+   <frame tileid="2" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="27">
+  <properties>
+   <property name="animation" value="adventurer_walk_up"/>
+  </properties>
+  <animation>
+   <frame tileid="27" duration="150"/>
+   <frame tileid="2" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="28">
+  <properties>
+   <property name="animation" value="adventurer_walk_right"/>
+  </properties>
+  <animation>
+   <frame tileid="28" duration="150"/>
+   <frame tileid="29" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="30">
+  <properties>
+   <property name="animation" value="adventurer_walk_left"/>
+  </properties>
+  <animation>
+   <frame tileid="30" duration="150"/>
+   <frame tileid="31" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="32">
+  <properties>
+   <property name="animation" value="adventurer_fall"/>
+  </properties>
+  <animation>
+   <frame tileid="32" duration="120"/>
+   <frame tileid="33" duration="120"/>
+   <frame tileid="34" duration="120"/>
+  </animation>
+ </tile>
+ <tile id="35">
+  <properties>
+   <property name="animation" value="adventurer_killed"/>
+  </properties>
+  <animation>
+   <frame tileid="35" duration="150"/>
+   <frame tileid="36" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="37">
+  <properties>
+   <property name="animation" value="adventurer_teleport"/>
+  </properties>
+  <animation>
+   <frame tileid="37" duration="80"/>
+   <frame tileid="38" duration="80"/>
+   <frame tileid="37" duration="80"/>
+  </animation>
+ </tile>
+ <tile id="39">
+  <properties>
+   <property name="animation" value="goblinoid_walk_down"/>
+  </properties>
+  <animation>
+   <frame tileid="39" duration="150"/>
+   <frame tileid="3" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="40">
+  <properties>
+   <property name="animation" value="goblinoid_walk_up"/>
+  </properties>
+  <animation>
+   <frame tileid="40" duration="150"/>
+   <frame tileid="3" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="41">
+  <properties>
+   <property name="animation" value="goblinoid_walk_right"/>
+  </properties>
+  <animation>
+   <frame tileid="41" duration="150"/>
+   <frame tileid="42" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="43">
+  <properties>
+   <property name="animation" value="goblinoid_walk_left"/>
+  </properties>
+  <animation>
+   <frame tileid="43" duration="150"/>
+   <frame tileid="44" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="45">
+  <properties>
+   <property name="animation" value="goblinoid_fall"/>
+  </properties>
+  <animation>
+   <frame tileid="45" duration="120"/>
+   <frame tileid="46" duration="120"/>
+   <frame tileid="47" duration="120"/>
+  </animation>
+ </tile>
+ <tile id="48">
+  <properties>
+   <property name="animation" value="goblinoid_killed"/>
+  </properties>
+  <animation>
+   <frame tileid="48" duration="150"/>
+   <frame tileid="49" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="50">
+  <properties>
+   <property name="animation" value="goblinoid_teleport"/>
+  </properties>
+  <animation>
+   <frame tileid="50" duration="80"/>
+   <frame tileid="51" duration="80"/>
+   <frame tileid="50" duration="80"/>
+  </animation>
+ </tile>
+ <tile id="58">
+  <properties>
+   <property name="animation" value="treasure_coin"/>
+  </properties>
+  <animation>
+   <frame tileid="58" duration="1500"/>
+   <frame tileid="59" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="60">
+  <properties>
+   <property name="animation" value="treasure_gem"/>
+  </properties>
+  <animation>
+   <frame tileid="60" duration="1500"/>
+   <frame tileid="61" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="62">
+  <properties>
+   <property name="animation" value="treasure_idol"/>
+  </properties>
+  <animation>
+   <frame tileid="62" duration="1500"/>
+   <frame tileid="63" duration="150"/>
+  </animation>
+ </tile>
+ <tile id="64">
+  <properties>
+   <property name="animation" value="planks_crumbling"/>
+  </properties>
+  <animation>
+   <frame tileid="64" duration="1000"/>
+  </animation>
+ </tile>
+ <tile id="65">
+  <properties>
+   <property name="animation" value="planks_crumbling_worn"/>
+  </properties>
+  <animation>
+   <frame tileid="65" duration="1000"/>
+  </animation>
+ </tile>
+ <tile id="66">
+  <properties>
+   <property name="animation" value="planks_timed"/>
+  </properties>
+  <animation>
+   <frame tileid="66" duration="1000"/>
+  </animation>
+ </tile>
+ <tile id="67">
+  <properties>
+   <property name="animation" value="planks_timed_ending"/>
+  </properties>
+  <animation>
+   <frame tileid="66" duration="100"/>
+   <frame tileid="67" duration="100"/>
+  </animation>
+ </tile>
 </tileset>
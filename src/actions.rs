@@ -0,0 +1,146 @@
+use bevy::{
+    app::AppExit,
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+};
+
+use crate::*;
+
+// Constants
+
+// the first map after the title
+const FIRST_LEVEL_IDX: usize = 1;
+
+// Plugin
+
+/// Menu buttons and in-world clickables that do something to the game as a
+/// whole, set up from the Tiled `action` property.
+pub struct ActionsPlugin;
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_hydrators).add_systems(
+            Update,
+            activate_action_areas.run_if(in_state(MapLoadState::Done)),
+        );
+    }
+}
+
+// Components
+
+#[derive(Debug, Clone, PartialEq, Component)]
+pub enum Action {
+    /// Play from the first level, with full lives
+    Start,
+    NextLevel,
+    /// Play the level the adventurer ran out of lives on, with full lives
+    Retry,
+    Quit,
+    /// Go to the map with this name, e.g. `goto:title`
+    GoTo(String),
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(map_name) = name.strip_prefix("goto:") {
+            return Some(Action::GoTo(map_name.into()));
+        }
+        return match name {
+            "start" => Some(Action::Start),
+            "next_level" => Some(Action::NextLevel),
+            "retry" => Some(Action::Retry),
+            "quit" => Some(Action::Quit),
+            _ => None,
+        };
+    }
+}
+
+#[derive(SystemParam)]
+pub struct ActionPerformer<'w, 's> {
+    audio_server: Res<'w, AudioServer>,
+    game_over: Res<'w, GameOver>,
+    lives_rule: Res<'w, LivesRule>,
+    lives: ResMut<'w, Lives>,
+    map_server: ResMut<'w, MapServer>,
+    next_state: ResMut<'w, NextState<SceneState>>,
+    ev_app_exit: EventWriter<'w, AppExit>,
+    commands: Commands<'w, 's>,
+}
+
+impl<'w, 's> ActionPerformer<'w, 's> {
+    pub fn perform(&mut self, action: &Action) {
+        self.commands
+            .spawn(self.audio_server.click.create_one_shot());
+
+        match action {
+            Action::Start => {
+                self.lives.0 = self.lives_rule.max_lives();
+                self.map_server.map_idx = FIRST_LEVEL_IDX;
+            }
+            Action::NextLevel => self.map_server.next_map(),
+            // only the game over map knows which level to go back to
+            Action::Retry
+                if self.map_server.maps[self.map_server.map_idx].name != GAME_OVER_MAP =>
+            {
+                println!("retry only works on the {} map", GAME_OVER_MAP);
+                return;
+            }
+            Action::Retry => {
+                self.lives.0 = self.lives_rule.max_lives();
+                self.map_server.map_idx = self.game_over.level_idx;
+            }
+            // there's nothing to quit to in a browser tab, so go to the title
+            Action::Quit if cfg!(target_arch = "wasm32") => self.map_server.go_to_first_map(),
+            Action::Quit => {
+                self.ev_app_exit.send(AppExit);
+                return;
+            }
+            Action::GoTo(map_name) => self.map_server.go_to_map(map_name),
+        }
+        self.next_state.set(SceneState::Transitioning);
+    }
+}
+
+// Hydrators
+
+fn hydrate_action(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    if let Some(action) = action_from_object(object_data) {
+        entity_commands.insert(action);
+    }
+}
+
+fn hydrate_action_button(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    if let Some(action) = action_from_object(object_data) {
+        hydrate_button(entity_commands, object_data, action);
+    }
+}
+
+// Systems
+
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators
+        .register_hydrator("Action", hydrate_action)
+        .register_hydrator("ActionButton", hydrate_action_button);
+}
+
+fn activate_action_areas(
+    mut ev_activate: EventReader<ActivateEvent>,
+    action_q: Query<&Action, With<ClickableArea>>,
+    mut action_performer: ActionPerformer,
+) {
+    for e in ev_activate.read() {
+        if let Ok(action) = action_q.get(e.0) {
+            action_performer.perform(action);
+        }
+    }
+}
+
+// Helpers
+
+fn action_from_object(object_data: &ObjectData) -> Option<Action> {
+    let name = get_property_value_from_object_or_default_s(object_data, "action", String::new());
+    let action = Action::from_name(&name);
+    if action.is_none() {
+        println!("unknown action:{} on {}", name, object_data.name);
+    }
+    return action;
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::NoTearDown;
+use crate::{filter_mover_kills, Adventurer, MoverKilled, NoTearDown};
 
 // Plugin
 
@@ -16,7 +16,8 @@ impl Plugin for AudioServerPlugin {
             pick_up: "sfx/pick_up.ogg",
             portal: "sfx/portal.ogg",
         })
-        .add_systems(Startup, load_audio);
+        .add_systems(Startup, load_audio)
+        .add_systems(FixedUpdate, play_kill_sounds.after(filter_mover_kills));
     }
 }
 
@@ -33,7 +34,7 @@ struct AudioFiles {
     portal: &'static str,
 }
 
-#[derive(Resource)]
+#[derive(Resource, Default)]
 pub struct AudioServer {
     pub click: PlayableAudioSource,
     pub die: PlayableAudioSource,
@@ -44,6 +45,7 @@ pub struct AudioServer {
     pub portal: PlayableAudioSource,
 }
 
+#[derive(Default)]
 pub struct PlayableAudioSource(Handle<AudioSource>);
 
 impl PlayableAudioSource {
@@ -97,3 +99,18 @@ fn load_audio(
     });
     commands.remove_resource::<AudioFiles>();
 }
+
+fn play_kill_sounds(
+    mut ev_mover_killed: EventReader<MoverKilled>,
+    adventurer_q: Query<(), With<Adventurer>>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
+) {
+    for kill in ev_mover_killed.read() {
+        if adventurer_q.contains(kill.victim) {
+            commands.spawn(audio_server.die.create_one_shot());
+        } else {
+            commands.spawn(audio_server.kill.create_one_shot());
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{map_loader::*, MAP_WIDTH_COORD};
 use bevy::asset::Handle;
 use bevy::prelude::*;
+use bevy_utils::HashMap;
 
 pub struct BRMapPlugin(pub Vec<String>);
 impl Plugin for BRMapPlugin {
@@ -50,17 +51,26 @@ impl MapServer {
     pub fn go_to_first_map(&mut self) {
         self.map_idx = 0;
     }
+    pub fn go_to_map(&mut self, name: &str) {
+        match self.maps.iter().position(|map| map.name == name) {
+            Some(idx) => self.map_idx = idx,
+            None => println!("tried to go to map:{} which was never loaded", name),
+        }
+    }
 }
 
 // Data
 #[derive(Debug)]
 pub struct MapData {
+    // file name without extension, e.g. "youwin"
+    pub name: String,
     pub width: usize,
     pub height: usize,
     pub tile_width: u16,
     pub data: Vec<u8>,
     pub objects: Vec<ObjectData>,
     pub sprite_sheet: TextureAtlasData,
+    pub tile_properties: HashMap<u32, Vec<ObjectProperty>>,
 }
 
 #[derive(Debug)]
@@ -73,6 +83,8 @@ pub struct ObjectData {
     pub x: u16,
     pub y: u16,
     pub z: f32,
+    pub width: f32,
+    pub height: f32,
     pub properties: Vec<ObjectProperty>,
 }
 
@@ -80,6 +92,8 @@ pub struct ObjectData {
 pub struct TextureAtlasData {
     pub tile_width: u8,
     pub columns: u32,
+    pub tile_animations: HashMap<u32, Vec<AnimationFrame>>,
+    pub animations: HashMap<String, Vec<AnimationFrame>>,
     pub sprite: Handle<Image>,
     pub texture_atlas_layout: Handle<TextureAtlasLayout>,
 }
@@ -146,10 +160,14 @@ fn create_map_server(
                 x: object_ref.x,
                 y: object_ref.y,
                 z,
+                width: object_ref.width.unwrap_or(template.width) as f32,
+                height: object_ref.height.unwrap_or(template.height) as f32,
                 sprite_idx: template.sprite_idx,
                 sprite_sheet: TextureAtlasData {
                     tile_width: template_sprite_sheet.tile_width,
                     columns: template_sprite_sheet.columns,
+                    tile_animations: template_sprite_sheet.tile_animations.clone(),
+                    animations: template_sprite_sheet.animations.clone(),
                     sprite: template_sprite_sheet.sprite.clone(),
                     texture_atlas_layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
                         Vec2::splat(template_sprite_sheet.tile_width.into()),
@@ -165,7 +183,14 @@ fn create_map_server(
 
         let map_sprite_sheet = spritesheet_assets.get(&asset.sprite_sheet).unwrap();
 
+        let name = map_handle
+            .path()
+            .and_then(|path| path.path().file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
         map_server.maps.push(MapData {
+            name,
             width: asset.width,
             height: asset.height,
             tile_width: asset.tile_width,
@@ -174,6 +199,8 @@ fn create_map_server(
             sprite_sheet: TextureAtlasData {
                 tile_width: map_sprite_sheet.tile_width,
                 columns: map_sprite_sheet.columns,
+                tile_animations: map_sprite_sheet.tile_animations.clone(),
+                animations: map_sprite_sheet.animations.clone(),
                 sprite: map_sprite_sheet.sprite.clone(),
                 texture_atlas_layout: texture_atlas_layouts.add(TextureAtlasLayout::from_grid(
                     Vec2::splat(map_sprite_sheet.tile_width.into()),
@@ -183,6 +210,7 @@ fn create_map_server(
                     None,
                 )),
             },
+            tile_properties: map_sprite_sheet.tile_properties.clone(),
         });
     }
 
@@ -1,7 +1,20 @@
-use bevy::ecs::system::EntityCommands;
+use bevy::ecs::system::{EntityCommands, SystemParam};
 
 use crate::*;
 
+// Constants
+
+// fingers are less precise than the mouse, so areas get bigger for touches
+const TOUCH_MARGIN: f32 = 4.0;
+// browsers fake mouse input for a tap a few frames after the finger lifts,
+// so the mouse is ignored for this long (in seconds) after any touch
+const EMULATED_MOUSE_DELAY: f32 = 0.5;
+const HOVER_COLOR: Color = Color::WHITE;
+const PRESS_COLOR: Color = Color::YELLOW;
+// what clicking the hovered area will make appear, or disappear
+pub const PREVIEW_ADD_COLOR: Color = Color::rgb(0.4, 1.0, 0.4);
+pub const PREVIEW_REMOVE_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+
 // Plugin
 
 pub struct ClickableAreaPlugin {
@@ -9,11 +22,14 @@ pub struct ClickableAreaPlugin {
 }
 impl Plugin for ClickableAreaPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<MouseClickEvent>()
+        app.add_event::<ActivateEvent>()
+            .init_resource::<HoveredArea>()
             .add_systems(Startup, add_hydrators)
             .add_systems(
                 Update,
-                track_clickable_areas.run_if(in_state(MapLoadState::Done)),
+                (track_clickable_areas, highlight_hovered_area)
+                    .chain()
+                    .run_if(in_state(MapLoadState::Done)),
             );
         if self.debug_clicks {
             app.add_systems(Update, log_clicks.run_if(in_state(MapLoadState::Done)));
@@ -25,39 +41,130 @@ fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
     hydrators.register_hydrator("ClickableArea", hydrate_clickable_area);
 }
 
+// Resources
+
+#[derive(Debug, Default, Resource)]
+pub struct HoveredArea {
+    pub entity: Option<Entity>,
+    // the mouse button is held down over it
+    pub pressed: bool,
+}
+
+impl HoveredArea {
+    /// The area whose effect should be previewed, under the mouse or else
+    /// the keyboard/gamepad focus.
+    pub fn previewed(&self, focus: &ClickableFocus) -> Option<Entity> {
+        return self.entity.or(focus.focused);
+    }
+}
+
+/// Mouse and touch input, keeping track of when a finger was last on the screen.
+#[derive(SystemParam)]
+pub struct PointerInput<'w, 's> {
+    buttons: Res<'w, ButtonInput<MouseButton>>,
+    touches: Res<'w, Touches>,
+    time: Res<'w, Time>,
+    last_touch: Local<'s, Option<f32>>,
+}
+
+impl<'w, 's> PointerInput<'w, 's> {
+    /// Whether touch is driving input right now, so the mouse should be ignored.
+    pub fn touching(&mut self) -> bool {
+        let now = self.time.elapsed_seconds();
+        if self.touches.iter().next().is_some()
+            || self.touches.iter_just_released().next().is_some()
+        {
+            *self.last_touch = Some(now);
+        }
+        return self
+            .last_touch
+            .is_some_and(|last_touch| now - last_touch < EMULATED_MOUSE_DELAY);
+    }
+}
+
+/// The window the pointer is in, and the camera to map it into the world with.
+#[derive(SystemParam)]
+pub struct PointerScreen<'w, 's> {
+    window_q: Query<'w, 's, &'static mut Window>,
+    camera_q: Query<'w, 's, (&'static Camera, &'static GlobalTransform), With<OuterCamera>>,
+}
+
+impl<'w, 's> PointerScreen<'w, 's> {
+    pub fn to_world(&self, screen_pos: Vec2) -> Option<Vec2> {
+        return self
+            .camera_q
+            .get_single()
+            .ok()
+            .and_then(|(camera, camera_transform)| {
+                camera
+                    .viewport_to_world(camera_transform, screen_pos)
+                    .map(|ray| ray.origin.truncate())
+            });
+    }
+
+    pub fn cursor_world_pos(&self) -> Option<Vec2> {
+        return self
+            .window_q
+            .get_single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+            .and_then(|screen_pos| self.to_world(screen_pos));
+    }
+
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        if let Ok(mut window) = self.window_q.get_single_mut() {
+            if window.cursor.icon != icon {
+                window.cursor.icon = icon;
+            }
+        }
+    }
+}
+
 // Events
 
+/// A `ClickableArea` was used, whether by mouse, keyboard or gamepad.
 #[derive(Event)]
-pub struct MouseClickEvent(pub Entity);
+pub struct ActivateEvent(pub Entity);
 
 // Components
 
 #[derive(Debug, Component)]
 pub struct ClickableArea {
     pub location: Vec2,
-    pub radius_squared: f32,
+    pub shape: CollisionShape,
     pub name: String,
+    // disabled areas can't be clicked
+    pub enabled: bool,
+    // where areas overlap, the highest z wins, then the nearest centre,
+    // then the highest priority
+    pub z: f32,
+    pub priority: i64,
 }
 
 pub fn hydrate_clickable_area(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
-    let radius = get_property_value_from_object_or_default_f(object_data, "radius", 0.5) as f32;
+    let (shape, offset) = collision_shape_from_object(object_data, "shape", "radius", 0.5);
+    let priority = get_property_value_from_object_or_default_i(object_data, "click_priority", 0);
+
     entity_commands.insert(ClickableArea {
         location: Vec2::new(
             coord_to_pos(object_data.x as f32),
             coord_to_pos(object_data.y as f32),
-        ),
-        radius_squared: radius * radius,
+        ) + offset,
+        shape,
         name: object_data.obj_type.clone(),
+        enabled: true,
+        z: object_data.z,
+        priority,
     });
 }
 
 // Systems
 
 fn log_clicks(
-    mut ev_mouse_click: EventReader<MouseClickEvent>,
+    mut ev_activate: EventReader<ActivateEvent>,
     clickable_area_q: Query<&ClickableArea>,
 ) {
-    for e in ev_mouse_click.read() {
+    for e in ev_activate.read() {
         if let Ok(clickable_area) = clickable_area_q.get(e.0) {
             println!("{:?} clicked!", clickable_area.name);
         }
@@ -65,38 +172,325 @@ fn log_clicks(
 }
 
 fn track_clickable_areas(
-    mut ev_mouse_click: EventWriter<MouseClickEvent>,
+    mut ev_activate: EventWriter<ActivateEvent>,
     area_q: Query<(Entity, &ClickableArea)>,
-    mut window_q: Query<&mut Window>,
-    camera_q: Query<(&Camera, &GlobalTransform), With<OuterCamera>>,
-    buttons: Res<ButtonInput<MouseButton>>,
+    mut screen: PointerScreen,
+    mut pointer: PointerInput,
+    mut hovered: ResMut<HoveredArea>,
+    pause_state: Res<State<PauseState>>,
 ) {
-    if let Ok(mut window) = window_q.get_single_mut() {
-        let mut hovering: Option<Entity> = None;
-        if let Some(mouse_postion) = window.cursor_position() {
-            if let Ok((camera, camera_transform)) = camera_q.get_single() {
-                let mouse_ray = camera.viewport_to_world(camera_transform, mouse_postion);
-                if let Some(mouse_ray) = mouse_ray {
-                    for (area_entity, area) in area_q.iter() {
-                        if mouse_ray.origin.truncate().distance_squared(area.location)
-                            <= area.radius_squared
-                        {
-                            hovering = Some(area_entity);
-                        }
-                    }
-                }
-            }
+    // menu buttons are clickable areas too, sitting above the level so they
+    // win any overlap, the only thing left to block clicks is pausing
+    if *pause_state.get() == PauseState::Paused {
+        *hovered = HoveredArea::default();
+        screen.set_cursor_icon(CursorIcon::Default);
+        return;
+    }
+
+    for world_pos in tapped_positions(&pointer.touches).filter_map(|pos| screen.to_world(pos)) {
+        if let Some(entity) = area_at(area_q.iter(), world_pos, TOUCH_MARGIN) {
+            ev_activate.send(ActivateEvent(entity));
         }
-        match hovering {
-            Some(entity) => {
-                window.cursor.icon = CursorIcon::Pointer;
-                if buttons.just_pressed(MouseButton::Left) {
-                    ev_mouse_click.send(MouseClickEvent(entity));
-                }
-            }
-            None => {
-                window.cursor.icon = CursorIcon::Default;
+    }
+    // there's nothing to hover with a finger, and the mouse clicks browsers
+    // fake for a tap must not activate anything a second time
+    if pointer.touching() {
+        *hovered = HoveredArea::default();
+        return;
+    }
+
+    let hovering = screen
+        .cursor_world_pos()
+        .and_then(|world_pos| area_at(area_q.iter(), world_pos, 0.0));
+    hovered.entity = hovering;
+    hovered.pressed = hovering.is_some() && pointer.buttons.pressed(MouseButton::Left);
+    match hovering {
+        Some(entity) => {
+            screen.set_cursor_icon(CursorIcon::Pointer);
+            if pointer.buttons.just_pressed(MouseButton::Left) {
+                ev_activate.send(ActivateEvent(entity));
             }
         }
+        None => {
+            screen.set_cursor_icon(CursorIcon::Default);
+        }
+    }
+}
+
+fn highlight_hovered_area(
+    hovered: Res<HoveredArea>,
+    area_q: Query<&ClickableArea>,
+    mut gizmos: Gizmos,
+) {
+    if let Some(area) = hovered.entity.and_then(|entity| area_q.get(entity).ok()) {
+        let color = if hovered.pressed {
+            PRESS_COLOR
+        } else {
+            HOVER_COLOR
+        };
+        draw_outline(&mut gizmos, area.location, &area.shape, color);
+    }
+}
+
+// Helpers
+
+pub fn draw_outline(gizmos: &mut Gizmos, pos: Vec2, shape: &CollisionShape, color: Color) {
+    match shape {
+        CollisionShape::Circle { radius } => {
+            gizmos.circle_2d(pos, *radius, color);
+        }
+        _ => {
+            gizmos.rect_2d(pos, 0.0, shape.half_extents() * 2.0, color);
+        }
+    }
+}
+
+// where fingers went down this frame, in screen space
+fn tapped_positions(touches: &Touches) -> impl Iterator<Item = Vec2> + '_ {
+    return touches.iter_just_pressed().map(|touch| touch.position());
+}
+
+fn area_at<'a>(
+    areas: impl Iterator<Item = (Entity, &'a ClickableArea)>,
+    world_pos: Vec2,
+    margin: f32,
+) -> Option<Entity> {
+    return areas
+        .filter(|(_, area)| {
+            area.enabled
+                && area
+                    .shape
+                    .inflated(margin)
+                    .contains_point(area.location, world_pos)
+        })
+        .max_by(|(a_entity, a), (b_entity, b)| {
+            a.z.total_cmp(&b.z)
+                .then(
+                    // nearer is better
+                    b.location
+                        .distance_squared(world_pos)
+                        .total_cmp(&a.location.distance_squared(world_pos)),
+                )
+                .then(a.priority.cmp(&b.priority))
+                // so ties don't depend on query order
+                .then(a_entity.cmp(b_entity))
+        })
+        .map(|(entity, _)| entity);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        input::{
+            mouse::MouseButtonInput,
+            touch::{TouchInput, TouchPhase},
+            ButtonState, InputPlugin,
+        },
+        render::camera::{camera_system, ManualTextureViews},
+        window::{PrimaryWindow, WindowCreated, WindowResized, WindowScaleFactorChanged},
+    };
+    use std::time::Duration;
+
+    fn area(x: f32, y: f32, radius: f32) -> ClickableArea {
+        return ClickableArea {
+            location: Vec2::new(x, y),
+            shape: CollisionShape::Circle { radius },
+            name: String::from("test"),
+            enabled: true,
+            z: 0.0,
+            priority: 0,
+        };
+    }
+
+    fn tap(app: &mut App, id: u64, phase: TouchPhase, position: Vec2) {
+        app.world.send_event(TouchInput {
+            phase,
+            position,
+            window: Entity::PLACEHOLDER,
+            force: None,
+            id,
+        });
+        app.update();
+    }
+
+    // a 1280x720 window looking at the world origin, so screen (640, 360) is world (0, 0)
+    fn pointer_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin)
+            .init_state::<PauseState>()
+            .init_resource::<Time>()
+            .init_resource::<HoveredArea>()
+            .init_resource::<Assets<Image>>()
+            .init_resource::<ManualTextureViews>()
+            .add_event::<ActivateEvent>()
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<AssetEvent<Image>>()
+            .add_systems(
+                Update,
+                (
+                    camera_system::<OrthographicProjection>,
+                    track_clickable_areas,
+                )
+                    .chain(),
+            );
+        let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+        app.world.spawn((Camera2dBundle::default(), OuterCamera));
+        app.update();
+        return (app, window);
+    }
+
+    fn click(app: &mut App, window: Entity, state: ButtonState, position: Vec2) {
+        app.world
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(position));
+        app.world.send_event(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        });
+        app.update();
+    }
+
+    fn activations(app: &App) -> Vec<Entity> {
+        let events = app.world.resource::<Events<ActivateEvent>>();
+        return events.get_reader().read(events).map(|e| e.0).collect();
+    }
+
+    #[test]
+    fn tapping_an_area_activates_it() {
+        let (mut app, _) = pointer_app();
+        let trigger = app.world.spawn(area(10.0, 20.0, 6.0)).id();
+        app.world.spawn(area(-40.0, 0.0, 6.0));
+
+        tap(&mut app, 0, TouchPhase::Started, Vec2::new(650.0, 340.0));
+        assert_eq!(activations(&app), vec![trigger]);
+
+        // lifting the finger doesn't activate it again
+        tap(&mut app, 0, TouchPhase::Ended, Vec2::new(650.0, 340.0));
+        app.update();
+        assert_eq!(activations(&app), vec![]);
+    }
+
+    #[test]
+    fn mouse_clicks_faked_after_a_tap_are_ignored() {
+        let (mut app, window) = pointer_app();
+        let trigger = app.world.spawn(area(10.0, 20.0, 6.0)).id();
+        let tap_pos = Vec2::new(650.0, 340.0);
+
+        tap(&mut app, 0, TouchPhase::Started, tap_pos);
+        tap(&mut app, 0, TouchPhase::Ended, tap_pos);
+        app.update();
+        click(&mut app, window, ButtonState::Pressed, tap_pos);
+        assert_eq!(activations(&app), vec![]);
+        click(&mut app, window, ButtonState::Released, tap_pos);
+
+        // a real click later on still works
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(EMULATED_MOUSE_DELAY * 2.0));
+        click(&mut app, window, ButtonState::Pressed, tap_pos);
+        assert_eq!(activations(&app), vec![trigger]);
+    }
+
+    #[test]
+    fn synthetic_taps_are_picked_up_once() {
+        let mut app = App::new();
+        app.add_plugins(InputPlugin);
+
+        tap(&mut app, 0, TouchPhase::Started, Vec2::new(10.0, 20.0));
+        let taps: Vec<Vec2> = tapped_positions(app.world.resource::<Touches>()).collect();
+        assert_eq!(taps, vec![Vec2::new(10.0, 20.0)]);
+
+        // holding the finger down isn't another tap
+        tap(&mut app, 0, TouchPhase::Moved, Vec2::new(11.0, 20.0));
+        assert_eq!(tapped_positions(app.world.resource::<Touches>()).count(), 0);
+
+        tap(&mut app, 0, TouchPhase::Ended, Vec2::new(11.0, 20.0));
+        tap(&mut app, 1, TouchPhase::Started, Vec2::new(30.0, 40.0));
+        let taps: Vec<Vec2> = tapped_positions(app.world.resource::<Touches>()).collect();
+        assert_eq!(taps, vec![Vec2::new(30.0, 40.0)]);
+    }
+
+    #[test]
+    fn touches_get_a_bigger_hit_area() {
+        let trigger = area(0.0, 0.0, 6.0);
+        let entity = Entity::from_raw(1);
+        let near_miss = Vec2::new(8.0, 0.0);
+
+        assert_eq!(
+            area_at([(entity, &trigger)].into_iter(), near_miss, 0.0),
+            None
+        );
+        assert_eq!(
+            area_at([(entity, &trigger)].into_iter(), near_miss, TOUCH_MARGIN),
+            Some(entity)
+        );
+    }
+
+    #[test]
+    fn overlapping_areas_pick_highest_z_then_nearest_then_priority() {
+        let (low, high, near, far) = (
+            Entity::from_raw(1),
+            Entity::from_raw(2),
+            Entity::from_raw(3),
+            Entity::from_raw(4),
+        );
+        let mut high_area = area(4.0, 0.0, 6.0);
+        high_area.z = 5.0;
+        let low_area = area(0.0, 0.0, 6.0);
+
+        // order doesn't matter
+        for areas in [
+            [(low, &low_area), (high, &high_area)],
+            [(high, &high_area), (low, &low_area)],
+        ] {
+            assert_eq!(area_at(areas.into_iter(), Vec2::ZERO, 0.0), Some(high));
+        }
+
+        let near_area = area(1.0, 0.0, 6.0);
+        let far_area = area(3.0, 0.0, 6.0);
+        for areas in [
+            [(near, &near_area), (far, &far_area)],
+            [(far, &far_area), (near, &near_area)],
+        ] {
+            assert_eq!(area_at(areas.into_iter(), Vec2::ZERO, 0.0), Some(near));
+        }
+
+        let plain_area = area(0.0, 0.0, 6.0);
+        let mut priority_area = area(0.0, 0.0, 6.0);
+        priority_area.priority = 1;
+        for areas in [
+            [(low, &plain_area), (high, &priority_area)],
+            [(high, &priority_area), (low, &plain_area)],
+        ] {
+            assert_eq!(area_at(areas.into_iter(), Vec2::ZERO, 0.0), Some(high));
+        }
+
+        let twin_area = area(0.0, 0.0, 6.0);
+        for areas in [
+            [(low, &plain_area), (high, &twin_area)],
+            [(high, &twin_area), (low, &plain_area)],
+        ] {
+            assert_eq!(area_at(areas.into_iter(), Vec2::ZERO, 0.0), Some(high));
+        }
+    }
+
+    #[test]
+    fn disabled_areas_ignore_taps() {
+        let mut trigger = area(0.0, 0.0, 6.0);
+        trigger.enabled = false;
+
+        assert_eq!(
+            area_at(
+                [(Entity::from_raw(1), &trigger)].into_iter(),
+                Vec2::ZERO,
+                TOUCH_MARGIN
+            ),
+            None
+        );
     }
 }
@@ -0,0 +1,194 @@
+use bevy::{input::gamepad::GamepadButton, prelude::*};
+
+use crate::*;
+
+// Constants
+
+const FOCUS_COLOR: Color = Color::rgba(1.0, 1.0, 0.5, 0.4);
+// how much bigger than the clickable area the highlight is, in pixels
+const FOCUS_MARGIN: f32 = 2.0;
+const FOCUS_Z: f32 = 50.0;
+
+const NEXT_KEYS: [KeyCode; 3] = [KeyCode::ArrowRight, KeyCode::ArrowDown, KeyCode::Tab];
+const PREVIOUS_KEYS: [KeyCode; 2] = [KeyCode::ArrowLeft, KeyCode::ArrowUp];
+const CONFIRM_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::NumpadEnter];
+const NEXT_BUTTONS: [GamepadButtonType; 3] = [
+    GamepadButtonType::DPadRight,
+    GamepadButtonType::DPadDown,
+    GamepadButtonType::RightTrigger,
+];
+const PREVIOUS_BUTTONS: [GamepadButtonType; 3] = [
+    GamepadButtonType::DPadLeft,
+    GamepadButtonType::DPadUp,
+    GamepadButtonType::LeftTrigger,
+];
+const CONFIRM_BUTTONS: [GamepadButtonType; 1] = [GamepadButtonType::South];
+
+// Plugin
+
+/// Lets keyboard and gamepad players pick a `ClickableArea` and use it.
+pub struct ClickableFocusPlugin;
+impl Plugin for ClickableFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClickableFocus>()
+            .add_systems(OnEnter(SceneState::Transitioning), clear_focus)
+            .add_systems(
+                Update,
+                (
+                    move_focus,
+                    activate_focus.run_if(in_state(PauseState::Running)),
+                    update_focus_highlight,
+                )
+                    .chain()
+                    .run_if(in_state(MapLoadState::Done)),
+            );
+    }
+}
+
+// Resources
+
+#[derive(Debug, Default, Resource)]
+pub struct ClickableFocus {
+    pub focused: Option<Entity>,
+}
+
+// Components
+
+#[derive(Debug, Component)]
+struct FocusHighlight;
+
+// Systems
+
+fn clear_focus(mut focus: ResMut<ClickableFocus>) {
+    focus.focused = None;
+}
+
+// areas are cycled in reading order, top to bottom then left to right
+fn move_focus(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    area_q: Query<(Entity, &ClickableArea)>,
+    mut focus: ResMut<ClickableFocus>,
+) {
+    let mut areas: Vec<(Entity, Vec2)> = area_q
+        .iter()
+        .filter(|(_, area)| area.enabled)
+        .map(|(entity, area)| (entity, area.location))
+        .collect();
+    areas.sort_by(|(_, a), (_, b)| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+
+    let focused_idx = focus
+        .focused
+        .and_then(|focused| areas.iter().position(|(entity, _)| *entity == focused));
+    // whatever was focused went away or was disabled
+    if focused_idx.is_none() && focus.focused.is_some() {
+        focus.focused = None;
+    }
+
+    let step = if pressed_any(
+        &keys,
+        &NEXT_KEYS,
+        &gamepads,
+        &gamepad_buttons,
+        &NEXT_BUTTONS,
+    ) {
+        1
+    } else if pressed_any(
+        &keys,
+        &PREVIOUS_KEYS,
+        &gamepads,
+        &gamepad_buttons,
+        &PREVIOUS_BUTTONS,
+    ) {
+        -1
+    } else {
+        0
+    };
+    if step == 0 || areas.is_empty() {
+        return;
+    }
+
+    let next_idx = match focused_idx {
+        Some(idx) => (idx as i32 + step).rem_euclid(areas.len() as i32) as usize,
+        None if step > 0 => 0,
+        None => areas.len() - 1,
+    };
+    focus.focused = Some(areas[next_idx].0);
+}
+
+fn activate_focus(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    focus: Res<ClickableFocus>,
+    mut ev_activate: EventWriter<ActivateEvent>,
+) {
+    if let Some(focused) = focus.focused {
+        if pressed_any(
+            &keys,
+            &CONFIRM_KEYS,
+            &gamepads,
+            &gamepad_buttons,
+            &CONFIRM_BUTTONS,
+        ) {
+            ev_activate.send(ActivateEvent(focused));
+        }
+    }
+}
+
+fn update_focus_highlight(
+    focus: Res<ClickableFocus>,
+    area_q: Query<&ClickableArea>,
+    mut highlight_q: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<FocusHighlight>>,
+    mut commands: Commands,
+) {
+    // the highlight goes with every scene tear down
+    if highlight_q.is_empty() {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FOCUS_COLOR,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PIXEL_PERFECT_LAYERS,
+            FocusHighlight,
+        ));
+        return;
+    }
+
+    if let Ok((mut transform, mut sprite, mut visibility)) = highlight_q.get_single_mut() {
+        match focus.focused.and_then(|focused| area_q.get(focused).ok()) {
+            Some(area) => {
+                transform.translation = area.location.extend(FOCUS_Z);
+                sprite.custom_size = Some(area.shape.half_extents() * 2.0 + FOCUS_MARGIN * 2.0);
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                *visibility = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+// Helpers
+
+fn pressed_any(
+    keys: &ButtonInput<KeyCode>,
+    key_codes: &[KeyCode],
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    button_types: &[GamepadButtonType],
+) -> bool {
+    if keys.any_just_pressed(key_codes.iter().copied()) {
+        return true;
+    }
+    return gamepads.iter().any(|gamepad| {
+        button_types.iter().any(|button_type| {
+            gamepad_buttons.just_pressed(GamepadButton::new(gamepad, *button_type))
+        })
+    });
+}
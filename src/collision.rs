@@ -1,8 +1,22 @@
 use crate::*;
 use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_utils::{HashMap, HashSet};
 
 // Constants
 
+pub const ALL_LAYERS: u32 = u32::MAX;
+
+// Named layers for the `collider_layers` and `collider_mask` properties, ex: "mover|hazard"
+const COLLISION_LAYERS: [(&str, u32); 7] = [
+    ("mover", 1 << 0),
+    ("treasure", 1 << 1),
+    ("hazard", 1 << 2),
+    ("platform", 1 << 3),
+    ("portal", 1 << 4),
+    ("exit", 1 << 5),
+    ("trigger", 1 << 6),
+];
+
 // Plugin
 pub struct CollisionPlugin {
     pub debug_collisions: bool,
@@ -12,7 +26,12 @@ impl Plugin for CollisionPlugin {
         app.add_event::<CollisionEnterEvent>()
             .add_event::<CollisionExitEvent>()
             .add_systems(Startup, add_hydrators)
-            .add_systems(Update, update_colliders);
+            .add_systems(
+                FixedUpdate,
+                update_colliders
+                    .after(move_movers)
+                    .run_if(in_state(RewindState::Idle)),
+            );
         if self.debug_collisions {
             app.add_systems(Update, (debug_collision_exit, debug_collision_enter));
         }
@@ -35,21 +54,110 @@ pub struct CollisionExitEvent(pub Entity, pub Entity);
 
 #[derive(Debug, Component)]
 pub struct Collider {
-    pub radius: f32,
+    pub shape: CollisionShape,
+    // from the entity's transform to the centre of the shape
+    pub offset: Vec2,
     pub name: String,
-    pub colliding_with: Vec<Entity>,
+    pub colliding_with: HashSet<Entity>,
     pub active: bool,
+    // what this collider is
+    pub layers: u32,
+    // what this collider wants to touch
+    pub mask: u32,
+}
+
+pub struct ColliderBody {
+    pub entity: Entity,
+    pub pos: Vec2,
+    pub shape: CollisionShape,
+    pub layers: u32,
+    pub mask: u32,
+}
+
+// Data
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionShape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+    FullTile,
+}
+
+impl CollisionShape {
+    pub fn half_extents(&self) -> Vec2 {
+        return match self {
+            CollisionShape::Circle { radius } => Vec2::splat(*radius),
+            CollisionShape::Aabb { half_extents } => *half_extents,
+            CollisionShape::FullTile => Vec2::splat(HALF_TILE_WIDTH * SCALE),
+        };
+    }
+
+    /// The same shape, `margin` bigger all around.
+    pub fn inflated(&self, margin: f32) -> CollisionShape {
+        return match self {
+            CollisionShape::Circle { radius } => CollisionShape::Circle {
+                radius: radius + margin,
+            },
+            _ => CollisionShape::Aabb {
+                half_extents: self.half_extents() + margin,
+            },
+        };
+    }
+
+    pub fn contains_point(&self, pos: Vec2, point: Vec2) -> bool {
+        return match self {
+            CollisionShape::Circle { radius } => pos.distance_squared(point) <= radius * radius,
+            _ => {
+                let delta = (point - pos).abs();
+                let half_extents = self.half_extents();
+                delta.x <= half_extents.x && delta.y <= half_extents.y
+            }
+        };
+    }
+
+    pub fn overlaps(&self, pos: Vec2, other: &CollisionShape, other_pos: Vec2) -> bool {
+        return match (self, other) {
+            (
+                CollisionShape::Circle { radius },
+                CollisionShape::Circle {
+                    radius: other_radius,
+                },
+            ) => {
+                let dist = radius + other_radius;
+                pos.distance_squared(other_pos) <= dist * dist
+            }
+            (CollisionShape::Circle { radius }, _) => {
+                circle_overlaps_box(pos, *radius, other_pos, other.half_extents())
+            }
+            (_, CollisionShape::Circle { radius }) => {
+                circle_overlaps_box(other_pos, *radius, pos, self.half_extents())
+            }
+            _ => {
+                let delta = (other_pos - pos).abs();
+                let reach = self.half_extents() + other.half_extents();
+                delta.x <= reach.x && delta.y <= reach.y
+            }
+        };
+    }
 }
 
 pub fn hydrate_collider(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
-    let radius = get_property_value_from_object_or_default_f(object_data, "collider_radius", 4.0);
+    let (shape, offset) =
+        collision_shape_from_object(object_data, "collider_shape", "collider_radius", 4.0);
     let active = get_property_value_from_object_or_default_b(object_data, "collider_active", true);
+    let layers =
+        get_property_value_from_object_or_default_s(object_data, "collider_layers", String::new());
+    let mask =
+        get_property_value_from_object_or_default_s(object_data, "collider_mask", String::new());
 
     entity_commands.insert(Collider {
-        radius: radius as f32,
+        shape,
+        offset,
         name: object_data.obj_type.clone(),
-        colliding_with: Vec::new(),
+        colliding_with: HashSet::new(),
         active,
+        layers: parse_layers(&layers),
+        mask: parse_layers(&mask),
     });
 }
 
@@ -60,51 +168,60 @@ pub fn update_colliders(
     mut ev_collision_exit: EventWriter<CollisionExitEvent>,
     mut colliders: Query<(Entity, &Transform, &mut Collider)>,
 ) {
-    let mut combinations = colliders.iter_combinations_mut::<2>();
-    while let Some([(entity1, transform1, mut collider1), (entity2, transform2, mut collider2)]) =
-        combinations.fetch_next()
-    {
-        let dist: f32 = collider1.radius + collider2.radius;
-        let dist2: f32 = dist * dist;
-        let both_active = collider1.active && collider2.active;
-
-        if !both_active
-            || (both_active
-                && transform1
-                    .translation
-                    .truncate()
-                    .distance_squared(transform2.translation.truncate())
-                    > dist2)
-        {
-            let pos1 = collider1.colliding_with.iter().position(|e| e.eq(&entity2));
-            let pos2 = collider2.colliding_with.iter().position(|e| e.eq(&entity1));
+    let bodies: Vec<ColliderBody> = colliders
+        .iter()
+        .filter(|(_, _, collider)| collider.active)
+        .map(|(entity, transform, collider)| ColliderBody {
+            entity,
+            pos: transform.translation.truncate() + collider.offset,
+            shape: collider.shape,
+            layers: collider.layers,
+            mask: collider.mask,
+        })
+        .collect();
 
-            let mut new_uncollision = false;
-            if let Some(pos1) = pos1 {
-                collider1.colliding_with.remove(pos1);
-                new_uncollision = true;
-            }
+    let touching = find_contacts(&bodies);
 
-            if let Some(pos2) = pos2 {
-                collider2.colliding_with.remove(pos2);
-                new_uncollision = true;
+    // contacts that ended, including ones with colliders that went inactive
+    let mut exits = HashSet::<(Entity, Entity)>::new();
+    for (entity, _, collider) in colliders.iter() {
+        for &other in collider.colliding_with.iter() {
+            let pair = ordered_pair(entity, other);
+            if !touching.contains(&pair) {
+                exits.insert(pair);
             }
+        }
+    }
+    let mut exits: Vec<(Entity, Entity)> = exits.into_iter().collect();
+    exits.sort();
 
-            if new_uncollision {
-                ev_collision_exit.send(CollisionExitEvent(entity1, entity2));
-            }
+    for (entity1, entity2) in exits {
+        if let Ok([(_, _, mut collider1), (_, _, mut collider2)]) =
+            colliders.get_many_mut([entity1, entity2])
+        {
+            collider1.colliding_with.remove(&entity2);
+            collider2.colliding_with.remove(&entity1);
+            ev_collision_exit.send(CollisionExitEvent(entity1, entity2));
         } else {
-            let mut new_collision = false;
-            if !collider1.colliding_with.contains(&entity2) {
-                collider1.colliding_with.push(entity2);
-                new_collision = true;
+            // the other collider was despawned
+            for entity in [entity1, entity2] {
+                if let Ok((_, _, mut collider)) = colliders.get_mut(entity) {
+                    collider.colliding_with.remove(&entity1);
+                    collider.colliding_with.remove(&entity2);
+                }
             }
+        }
+    }
 
-            if !collider2.colliding_with.contains(&entity1) {
-                collider2.colliding_with.push(entity1);
-                new_collision = true;
-            }
+    let mut enters: Vec<(Entity, Entity)> = touching.into_iter().collect();
+    enters.sort();
 
+    for (entity1, entity2) in enters {
+        if let Ok([(_, _, mut collider1), (_, _, mut collider2)]) =
+            colliders.get_many_mut([entity1, entity2])
+        {
+            let new_collision =
+                collider1.colliding_with.insert(entity2) | collider2.colliding_with.insert(entity1);
             if new_collision {
                 ev_collision_enter.send(CollisionEnterEvent(entity1, entity2));
             }
@@ -135,3 +252,260 @@ pub fn debug_collision_exit(
 }
 
 // Helpers
+
+/// Uniform grid broadphase keyed on tile coordinates. Returns every pair of
+/// bodies whose shapes (circles, boxes or whole tiles) overlap and whose
+/// layers are each in the other's mask, with the smaller entity first.
+pub fn find_contacts(bodies: &[ColliderBody]) -> HashSet<(Entity, Entity)> {
+    let mut grid = HashMap::<IVec2, Vec<usize>>::new();
+    for (idx, body) in bodies.iter().enumerate() {
+        let half_extents = body.shape.half_extents();
+        let min = pos_to_cell(body.pos - half_extents);
+        let max = pos_to_cell(body.pos + half_extents);
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                grid.entry(IVec2::new(x, y)).or_default().push(idx);
+            }
+        }
+    }
+
+    let mut contacts = HashSet::<(Entity, Entity)>::new();
+    for cell in grid.values() {
+        for (i, &idx1) in cell.iter().enumerate() {
+            for &idx2 in &cell[i + 1..] {
+                let (body1, body2) = (&bodies[idx1], &bodies[idx2]);
+                if !layers_interact(body1.layers, body1.mask, body2.layers, body2.mask) {
+                    continue;
+                }
+                if body1.shape.overlaps(body1.pos, &body2.shape, body2.pos) {
+                    contacts.insert(ordered_pair(body1.entity, body2.entity));
+                }
+            }
+        }
+    }
+
+    return contacts;
+}
+
+/// Reads a shape name ("circle", "box" or "tile") and circle radius, sizing
+/// boxes from the Tiled object. Tile objects grow right and up from their
+/// anchor tile, so the returned offset points at the shape's centre.
+pub fn collision_shape_from_object(
+    object_data: &ObjectData,
+    shape_property: &str,
+    radius_property: &str,
+    default_radius: f64,
+) -> (CollisionShape, Vec2) {
+    let shape_name =
+        get_property_value_from_object_or_default_s(object_data, shape_property, "circle".into());
+    let radius =
+        get_property_value_from_object_or_default_f(object_data, radius_property, default_radius)
+            as f32;
+
+    let size = Vec2::new(object_data.width, object_data.height) * SCALE;
+    let offset = (size - Vec2::splat(TILE_WIDTH * SCALE)) / 2.0;
+
+    let shape = match shape_name.as_str() {
+        "box" => CollisionShape::Aabb {
+            half_extents: size / 2.0,
+        },
+        "tile" => CollisionShape::FullTile,
+        "circle" => CollisionShape::Circle { radius },
+        val => {
+            println!("unknown collision shape:{}, using circle", val);
+            CollisionShape::Circle { radius }
+        }
+    };
+
+    return (shape, offset);
+}
+
+pub fn layers_interact(layers1: u32, mask1: u32, layers2: u32, mask2: u32) -> bool {
+    return layers1 & mask2 != 0 && layers2 & mask1 != 0;
+}
+
+/// Parses "mover|hazard" style layer lists. An empty list means every layer.
+pub fn parse_layers(layer_names: &str) -> u32 {
+    if layer_names.is_empty() {
+        return ALL_LAYERS;
+    }
+
+    let mut layers = 0;
+    for layer_name in layer_names.split("|") {
+        match COLLISION_LAYERS
+            .iter()
+            .find(|(name, _)| *name == layer_name)
+        {
+            Some((_, layer)) => layers |= layer,
+            None => println!("tried to parse unknown collision layer:{}", layer_name),
+        }
+    }
+    return layers;
+}
+
+fn circle_overlaps_box(circle_pos: Vec2, radius: f32, box_pos: Vec2, half_extents: Vec2) -> bool {
+    let closest = circle_pos.clamp(box_pos - half_extents, box_pos + half_extents);
+    return circle_pos.distance_squared(closest) <= radius * radius;
+}
+
+fn pos_to_cell(pos: Vec2) -> IVec2 {
+    return (pos / TILE_WIDTH).floor().as_ivec2();
+}
+
+fn ordered_pair(entity1: Entity, entity2: Entity) -> (Entity, Entity) {
+    if entity1 < entity2 {
+        return (entity1, entity2);
+    }
+    return (entity2, entity1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn body(id: u32, pos: Vec2, shape: CollisionShape, layers: &str, mask: &str) -> ColliderBody {
+        return ColliderBody {
+            entity: Entity::from_raw(id),
+            pos,
+            shape,
+            layers: parse_layers(layers),
+            mask: parse_layers(mask),
+        };
+    }
+
+    fn circle(radius: f32) -> CollisionShape {
+        return CollisionShape::Circle { radius };
+    }
+
+    fn pair(id1: u32, id2: u32) -> (Entity, Entity) {
+        return ordered_pair(Entity::from_raw(id1), Entity::from_raw(id2));
+    }
+
+    fn brute_force_contacts(bodies: &[ColliderBody]) -> HashSet<(Entity, Entity)> {
+        let mut contacts = HashSet::<(Entity, Entity)>::new();
+        for (i, body1) in bodies.iter().enumerate() {
+            for body2 in &bodies[i + 1..] {
+                if layers_interact(body1.layers, body1.mask, body2.layers, body2.mask)
+                    && body1.shape.overlaps(body1.pos, &body2.shape, body2.pos)
+                {
+                    contacts.insert(ordered_pair(body1.entity, body2.entity));
+                }
+            }
+        }
+        return contacts;
+    }
+
+    #[test]
+    fn contacts_straddling_cell_boundaries_are_found() {
+        let bodies = [
+            // either side of x = TILE_WIDTH
+            body(0, Vec2::new(14.0, 4.0), circle(2.0), "", ""),
+            body(1, Vec2::new(18.0, 4.0), circle(2.0), "", ""),
+            // either side of the origin, diagonally
+            body(2, Vec2::new(-0.5, -0.5), circle(1.0), "", ""),
+            body(3, Vec2::new(0.5, 0.5), circle(1.0), "", ""),
+            // a box reaching into the next row of cells
+            body(
+                4,
+                Vec2::new(40.0, 12.0),
+                CollisionShape::Aabb {
+                    half_extents: Vec2::new(2.0, 6.0),
+                },
+                "",
+                "",
+            ),
+            body(5, Vec2::new(40.0, 20.0), circle(2.0), "", ""),
+            // a whole tile straddling four cells
+            body(6, Vec2::new(-64.0, -64.0), CollisionShape::FullTile, "", ""),
+            body(7, Vec2::new(-57.0, -57.0), circle(1.0), "", ""),
+        ];
+
+        let contacts = find_contacts(&bodies);
+        assert_eq!(
+            contacts,
+            HashSet::from_iter([pair(0, 1), pair(2, 3), pair(4, 5), pair(6, 7)])
+        );
+    }
+
+    #[test]
+    fn contacts_respect_layers_and_masks() {
+        let pos = Vec2::new(8.0, 8.0);
+        let bodies = [
+            body(0, pos, circle(4.0), "mover", "hazard|trigger"),
+            body(1, pos, circle(4.0), "hazard", "mover"),
+            // wants movers, but the first mover doesn't want treasure
+            body(2, pos, circle(4.0), "treasure", "mover"),
+            // a mover that only wants treasure, so the hazard is ignored
+            body(3, pos, circle(4.0), "mover", "treasure"),
+        ];
+
+        let contacts = find_contacts(&bodies);
+        assert_eq!(contacts, HashSet::from_iter([pair(0, 1), pair(2, 3)]));
+    }
+
+    #[test]
+    fn contacts_match_brute_force() {
+        let layer_names = ["mover", "treasure", "hazard", "trigger", ""];
+        let mut rng = StdRng::seed_from_u64(31);
+        let bodies: Vec<ColliderBody> = (0..400)
+            .map(|i| {
+                let pos = Vec2::new(rng.gen_range(-80.0..80.0), rng.gen_range(-80.0..80.0));
+                let shape = match i % 3 {
+                    0 => circle(rng.gen_range(1.0..10.0)),
+                    1 => CollisionShape::Aabb {
+                        half_extents: Vec2::new(rng.gen_range(1.0..12.0), rng.gen_range(1.0..12.0)),
+                    },
+                    _ => CollisionShape::FullTile,
+                };
+                let layers = layer_names[rng.gen_range(0..layer_names.len())];
+                let mask = layer_names[rng.gen_range(0..layer_names.len())];
+                body(i, pos, shape, layers, mask)
+            })
+            .collect();
+
+        let contacts = find_contacts(&bodies);
+        assert!(!contacts.is_empty());
+        assert_eq!(contacts, brute_force_contacts(&bodies));
+    }
+}
+
+#[cfg(test)]
+mod benches {
+    use super::*;
+    use std::time::Instant;
+
+    // cargo test --release broadphase_scaling -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn broadphase_scaling() {
+        for count in [100, 1_000, 10_000] {
+            // a tight grid, so neighbours overlap like pits and planks do
+            let side = (count as f32).sqrt().ceil() as u32;
+            let bodies: Vec<ColliderBody> = (0..count)
+                .map(|i| ColliderBody {
+                    entity: Entity::from_raw(i),
+                    pos: Vec2::new((i % side) as f32, (i / side) as f32) * HALF_TILE_WIDTH,
+                    shape: CollisionShape::Circle {
+                        radius: 4.0 + (i % 3) as f32,
+                    },
+                    layers: ALL_LAYERS,
+                    mask: ALL_LAYERS,
+                })
+                .collect();
+
+            let iterations = 20;
+            let start = Instant::now();
+            let mut contacts = 0;
+            for _ in 0..iterations {
+                contacts = find_contacts(&bodies).len();
+            }
+            println!(
+                "{:>6} colliders: {:>10.3?} per frame ({} contacts)",
+                count,
+                start.elapsed() / iterations,
+                contacts
+            );
+        }
+    }
+}
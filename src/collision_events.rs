@@ -1,6 +1,6 @@
 use crate::*;
 use bevy::{
-    ecs::query::{QueryData, QueryFilter},
+    ecs::system::{EntityCommands, SystemParam},
     prelude::*,
 };
 
@@ -10,26 +10,42 @@ pub struct CollisionEventsPlugin;
 impl Plugin for CollisionEventsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, add_hydrators)
-            .add_systems(
-                Update,
-                (
-                    on_adventurer_goblinoid_collide,
-                    on_mover_treasure_collide,
-                    on_adventurer_exit_collide.run_if(in_state(MapLoadState::Done)),
-                    on_mover_portal_collide,
-                ),
+            .add_collision_rule::<Adventurer, Goblinoid, _>(
+                CollisionPhase::Enter,
+                on_adventurer_goblinoid_collide,
             )
-            .add_systems(
-                FixedUpdate,
-                (
-                    on_mover_planks_collide,
-                    on_mover_planks_uncollide,
-                    on_mover_pit_collide,
-                    on_mover_pit_uncollide,
-                    movers_fall_into_pits,
-                )
-                    .chain(),
-            );
+            .add_collision_rule::<Mover, Treasure, _>(
+                CollisionPhase::Enter,
+                on_mover_treasure_collide,
+            )
+            .add_collision_rule::<Adventurer, Exit, _>(
+                CollisionPhase::Enter,
+                on_adventurer_exit_collide,
+            )
+            .add_collision_rule::<Mover, EnterPortal, _>(
+                CollisionPhase::Enter,
+                on_mover_portal_collide,
+            )
+            .add_collision_rule::<Mover, EnterPortal, _>(
+                CollisionPhase::Exit,
+                on_mover_portal_uncollide,
+            )
+            .add_collision_rule::<Mover, Trap, _>(CollisionPhase::Enter, on_mover_trap_collide)
+            .add_collision_rule::<Mover, Key, _>(CollisionPhase::Enter, on_mover_key_collide)
+            .add_collision_rule::<Mover, Door, _>(CollisionPhase::Enter, on_mover_door_collide)
+            .add_collision_rule::<Mover, PressurePlate, _>(
+                CollisionPhase::Enter,
+                on_mover_pressure_plate_collide,
+            )
+            // dead movers lose `Mover` but keep their `KeyRing`, so they still let go
+            .add_collision_rule::<KeyRing, PressurePlate, _>(
+                CollisionPhase::Exit,
+                on_mover_pressure_plate_uncollide,
+            )
+            .add_collision_rule::<Mover, Planks, _>(CollisionPhase::Enter, on_mover_planks_collide)
+            .add_collision_rule::<Mover, Planks, _>(CollisionPhase::Exit, on_mover_planks_uncollide)
+            .add_collision_rule::<Mover, Pit, _>(CollisionPhase::Enter, on_mover_pit_collide)
+            .add_collision_rule::<Mover, Pit, _>(CollisionPhase::Exit, on_mover_pit_uncollide);
     }
 }
 
@@ -39,8 +55,88 @@ impl Plugin for CollisionEventsPlugin {
 pub struct Goblinoid;
 #[derive(Default, Component)]
 pub struct Adventurer;
+/// Without `min_score` or `required_kind` every treasure on the map has to be
+/// carried out, otherwise only those conditions do.
+#[derive(Default, Component)]
+pub struct Exit {
+    pub min_score: Option<u32>,
+    pub required_kind: Option<TreasureKind>,
+}
 #[derive(Default, Component)]
-pub struct Exit;
+pub struct Trap;
+
+/// What an exit checks before it lets an adventurer through.
+#[derive(SystemParam)]
+pub struct ExitRequirements<'w, 's> {
+    exit_q: Query<'w, 's, &'static Exit>,
+    treasure_q: Query<'w, 's, &'static Treasure>,
+    treasure_train_q: Query<'w, 's, &'static TreasureTrain>,
+    treasure_count: Res<'w, TreasureCount>,
+    level_score: Res<'w, LevelScore>,
+}
+
+impl<'w, 's> ExitRequirements<'w, 's> {
+    /// Whether `adventurer_entity` may leave through `exit_entity`, `None` if it isn't an exit.
+    pub fn met(&self, exit_entity: Entity, adventurer_entity: Entity) -> Option<bool> {
+        let exit = match self.exit_q.get(exit_entity) {
+            Ok(exit) => exit,
+            Err(_) => return None,
+        };
+
+        if exit.min_score.is_none() && exit.required_kind.is_none() {
+            return Some(self.treasure_count.map_treasures == self.treasure_count.player_treasures);
+        }
+
+        let carried: Vec<Entity> = self
+            .treasure_train_q
+            .iter()
+            .filter(|treasure_train| treasure_train.mover == adventurer_entity)
+            .flat_map(|treasure_train| treasure_train.treasures.clone())
+            .collect();
+
+        let has_score = match exit.min_score {
+            Some(min_score) => self.level_score.total() >= min_score,
+            None => true,
+        };
+        let has_kind = match exit.required_kind {
+            Some(kind) => {
+                let on_map = self.treasure_q.iter().filter(|t| t.kind == kind).count();
+                let carried_kind = self
+                    .treasure_q
+                    .iter_many(&carried)
+                    .filter(|t| t.kind == kind)
+                    .count();
+                carried_kind == on_map
+            }
+            None => true,
+        };
+        return Some(has_score && has_kind);
+    }
+}
+
+/// Both ends of the teleporters a mover can walk into.
+#[derive(SystemParam)]
+pub struct Portals<'w, 's> {
+    enter_portal_q: Query<'w, 's, (&'static EnterPortal, Option<&'static ExitPortal>)>,
+    exit_portal_q: Query<'w, 's, (&'static Transform, &'static ExitPortal), Without<Mover>>,
+}
+
+// Hydrators
+
+fn hydrate_exit(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let min_score = get_property_value_from_object_or_default_i(object_data, "min_score", 0);
+    let required_kind =
+        get_property_value_from_object_or_default_s(object_data, "required_kind", String::new());
+
+    entity_commands.insert(Exit {
+        min_score: if min_score > 0 {
+            Some(min_score as u32)
+        } else {
+            None
+        },
+        required_kind: TreasureKind::from_name(&required_kind),
+    });
+}
 
 // Systems
 
@@ -48,207 +144,438 @@ fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
     hydrators
         .register_tag::<Goblinoid>("Goblinoid")
         .register_tag::<Adventurer>("Adventurer")
-        .register_tag::<Treasure>("Treasure")
-        .register_tag::<Exit>("Exit");
+        .register_hydrator("Exit", hydrate_exit)
+        .register_tag::<Trap>("Trap");
 }
 
 pub fn on_adventurer_goblinoid_collide(
-    mut commands: Commands,
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    adventurer_q: Query<Entity, With<Adventurer>>,
-    mut goblinoid_q: Query<Entity, With<Goblinoid>>,
-    audio_server: Res<AudioServer>,
+    In((adventurer_entity, goblinoid_entity)): In<(Entity, Entity)>,
+    mut ev_mover_killed: EventWriter<MoverKilled>,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &adventurer_q);
-
-        if let (Ok(adventurer_entity), Ok(goblinoid_entity)) =
-            (adventurer_q.get(entity1), goblinoid_q.get_mut(entity2))
-        {
-            commands.entity(goblinoid_entity).insert(Dead {
-                killed_by: Some(adventurer_entity),
-                fell_into_pit: false,
-            });
-            commands.spawn(audio_server.kill.create_one_shot());
-        }
-    }
+    ev_mover_killed.send(MoverKilled {
+        victim: goblinoid_entity,
+        cause: KillCause::Goblinoid,
+        killer: Some(adventurer_entity),
+    });
 }
 
 pub fn on_mover_treasure_collide(
+    In((mover_entity, treasure_entity)): In<(Entity, Entity)>,
     mut commands: Commands,
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    mover_q: Query<(Entity, &Mover)>,
-    mut treasure_q: Query<(Entity, &mut Collider, &mut Treasure)>,
+    mover_q: Query<&Transform, With<Mover>>,
+    mut treasure_q: Query<&mut Collider, With<Treasure>>,
     audio_server: Res<AudioServer>,
     mut treasure_train_q: Query<&mut TreasureTrain>,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
-        if let (
-            Ok((mover_entity, mover)),
-            Ok((treasure_entity, mut treasure_collider, mut treasure)),
-        ) = (mover_q.get(entity1), treasure_q.get_mut(entity2))
-        {
-            treasure_collider.active = false;
+    if let (Ok(mover_transform), Ok(mut treasure_collider)) = (
+        mover_q.get(mover_entity),
+        treasure_q.get_mut(treasure_entity),
+    ) {
+        treasure_collider.active = false;
 
-            commands.spawn(audio_server.pick_up.create_one_shot());
+        commands.spawn(audio_server.pick_up.create_one_shot());
 
-            let mut found_treasure_train: Option<Mut<TreasureTrain>> = None;
+        let mut found_treasure_train: Option<Mut<TreasureTrain>> = None;
 
-            for tr in treasure_train_q.iter_mut() {
-                if tr.mover == mover_entity {
-                    found_treasure_train = Some(tr);
-                    break;
-                }
+        for tr in treasure_train_q.iter_mut() {
+            if tr.mover == mover_entity {
+                found_treasure_train = Some(tr);
+                break;
             }
+        }
 
-            if let Some(mut treasure_train_entity) = found_treasure_train {
-                treasure_train_entity.treasures.push(treasure_entity);
-            } else {
-                commands.spawn(TreasureTrain {
-                    mover: mover_entity,
-                    treasures: vec![treasure_entity],
-                    head_spot: mover.target,
-                    target_spots: vec![mover.coord],
-                });
-            }
+        if let Some(mut treasure_train_entity) = found_treasure_train {
+            treasure_train_entity.treasures.push(treasure_entity);
+        } else {
+            commands.spawn(TreasureTrain::new(
+                mover_entity,
+                treasure_entity,
+                mover_transform.translation.truncate(),
+            ));
         }
     }
 }
 
 pub fn on_adventurer_exit_collide(
+    In((adventurer_entity, exit_entity)): In<(Entity, Entity)>,
     mut commands: Commands,
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    adventurer_q: Query<Entity, With<Adventurer>>,
-    exit_q: Query<&Exit>,
     audio_server: Res<AudioServer>,
     mut next_state: ResMut<NextState<SceneState>>,
     mut map_server: ResMut<MapServer>,
-    treasure_count: ResMut<TreasureCount>,
+    exit_requirements: ExitRequirements,
+    mut ev_mover_killed: EventWriter<MoverKilled>,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &adventurer_q);
-        if let (Ok(entity), Ok(_)) = (adventurer_q.get(entity1), exit_q.get(entity2)) {
-            if treasure_count.map_treasures == treasure_count.player_treasures {
-                commands.spawn(audio_server.exit.create_one_shot());
-                map_server.next_map();
-                next_state.set(SceneState::Transitioning);
-            } else {
-                commands.spawn(audio_server.die.create_one_shot());
-                commands.entity(entity).insert(Dead {
-                    killed_by: None,
-                    fell_into_pit: false,
-                });
-            }
-        }
+    let can_exit = match exit_requirements.met(exit_entity, adventurer_entity) {
+        Some(can_exit) => can_exit,
+        None => return,
+    };
+
+    if can_exit {
+        commands.spawn(audio_server.exit.create_one_shot());
+        map_server.next_map();
+        next_state.set(SceneState::Transitioning);
+    } else {
+        ev_mover_killed.send(MoverKilled {
+            victim: adventurer_entity,
+            cause: KillCause::ExitWithoutTreasure,
+            killer: None,
+        });
     }
 }
 
 pub fn on_mover_portal_collide(
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    mut mover_q: Query<(&mut Mover)>,
-    portal_q: Query<&EnterPortal>,
-    exit_portal_q: Query<(&Transform, &ExitPortal), Without<Mover>>,
+    In((mover_entity, portal_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<(
+        &mut Mover,
+        Option<(&mut MoverAnimations, &mut SpriteAnimation)>,
+    )>,
+    portals: Portals,
+    cooldown_q: Query<&PortalCooldown>,
+    mut treasure_train_q: Query<&mut TreasureTrain>,
     audio_server: Res<AudioServer>,
     mut commands: Commands,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
-
-        if let (Ok(mut mover), Ok(portal)) = (mover_q.get_mut(entity1), portal_q.get(entity2)) {
-            if let Some(exit_entity) = portal.exit_portal {
-                if let Ok((exit_transform, exit_portal)) = exit_portal_q.get(exit_entity) {
-                    let teleport_start_coord = mover.target;
-                    let teleport_end_coord = IVec2::new(
-                        pos_to_coord(exit_transform.translation.x) as i32,
-                        pos_to_coord(exit_transform.translation.y) as i32,
-                    );
-
-                    mover.coord = teleport_start_coord;
-                    mover.target = teleport_end_coord;
-                    mover.dir = exit_portal.exit_dir;
-                    mover.move_percent = -(1.0 - mover.move_percent); // :D
-
-                    commands.spawn(audio_server.portal.create_one_shot());
+    if let Ok(cooldown) = cooldown_q.get(mover_entity) {
+        if cooldown.blocks(portal_entity) {
+            return;
+        }
+    }
+
+    if let (Ok((mut mover, animations)), Ok((portal, portal_as_exit))) = (
+        mover_q.get_mut(mover_entity),
+        portals.enter_portal_q.get(portal_entity),
+    ) {
+        // the far end of a two way pair only works while its teleporter points at it
+        if portal_as_exit.is_some_and(|exit| !exit.active) {
+            return;
+        }
+
+        if let Some(exit_entity) = portal.exit_portal {
+            if let Ok((exit_transform, exit_portal)) = portals.exit_portal_q.get(exit_entity) {
+                let teleport_start_coord = mover.target;
+                let teleport_end_coord = IVec2::new(
+                    pos_to_coord(exit_transform.translation.x) as i32,
+                    pos_to_coord(exit_transform.translation.y) as i32,
+                );
+
+                mover.coord = teleport_start_coord;
+                mover.target = teleport_end_coord;
+                mover.dir = exit_portal.exit_dir;
+                mover.move_percent = -(1.0 - mover.move_percent); // :D
+
+                if let Some((mut mover_animations, mut sprite_animation)) = animations {
+                    mover_animations.play(MoverAnimation::Teleport, &mut sprite_animation);
                 }
+
+                for mut treasure_train in treasure_train_q.iter_mut() {
+                    if treasure_train.mover == mover_entity {
+                        treasure_train.teleport(exit_transform.translation.truncate());
+                    }
+                }
+
+                // only the far end of a two way pair can send it straight back
+                let arrived_through = if portals.enter_portal_q.contains(exit_entity) {
+                    Some(exit_entity)
+                } else {
+                    None
+                };
+                commands
+                    .entity(mover_entity)
+                    .insert(PortalCooldown::new(arrived_through, exit_portal.cooldown));
+                commands.spawn(audio_server.portal.create_one_shot());
             }
         }
     }
 }
 
-pub fn on_mover_pit_collide(
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    mut mover_q: Query<&mut OverPitCounter, With<Mover>>,
-    pit_q: Query<&Pit, Without<Mover>>,
+// the portal a mover came out of works again once the mover has left it
+pub fn on_mover_portal_uncollide(
+    In((mover_entity, portal_entity)): In<(Entity, Entity)>,
+    mut cooldown_q: Query<&mut PortalCooldown>,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
-
-        if let (Ok(mut over_pit_counter), Ok(_)) = (mover_q.get_mut(entity1), pit_q.get(entity2)) {
-            over_pit_counter.0 += 1;
+    if let Ok(mut cooldown) = cooldown_q.get_mut(mover_entity) {
+        if cooldown.arrived_through == Some(portal_entity) {
+            cooldown.arrived_through = None;
         }
     }
 }
 
-pub fn on_mover_pit_uncollide(
-    mut ev_collision_exit: EventReader<CollisionExitEvent>,
-    mut mover_q: Query<&mut OverPitCounter, With<Mover>>,
-    pit_q: Query<&Pit, Without<Mover>>,
+pub fn on_mover_trap_collide(
+    In((mover_entity, _)): In<(Entity, Entity)>,
+    mut ev_mover_killed: EventWriter<MoverKilled>,
+) {
+    ev_mover_killed.send(MoverKilled {
+        victim: mover_entity,
+        cause: KillCause::Trap,
+        killer: None,
+    });
+}
+
+pub fn on_mover_key_collide(
+    In((mover_entity, key_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut KeyRing, With<Mover>>,
+    mut key_q: Query<(&mut Collider, &mut Visibility, &Key), Without<Mover>>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
 ) {
-    for e in ev_collision_exit.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
+    if let (Ok(mut key_ring), Ok((mut key_collider, mut key_visibility, key))) =
+        (mover_q.get_mut(mover_entity), key_q.get_mut(key_entity))
+    {
+        key_collider.active = false;
+        *key_visibility = Visibility::Hidden;
+        key_ring.keys.push(key_entity);
+        key_ring.key_ids.push(key.id as i64);
 
-        if let (Ok(mut over_pit_counter), Ok(_)) = (mover_q.get_mut(entity1), pit_q.get(entity2)) {
-            over_pit_counter.0 -= 1;
+        commands.spawn(audio_server.pick_up.create_one_shot());
+    }
+}
+
+pub fn on_mover_door_collide(
+    In((mover_entity, door_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut KeyRing, With<Mover>>,
+    mut door_q: Query<&mut Door, Without<Mover>>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
+) {
+    if let (Ok(mut key_ring), Ok(mut door)) =
+        (mover_q.get_mut(mover_entity), door_q.get_mut(door_entity))
+    {
+        if door.open {
+            return;
+        }
+        if let Some(idx) = key_ring.key_ids.iter().position(|id| *id == door.key_id) {
+            key_ring.keys.remove(idx);
+            key_ring.key_ids.remove(idx);
+            door.open = true;
+
+            commands.spawn(audio_server.click.create_one_shot());
         }
     }
 }
 
-pub fn on_mover_planks_collide(
-    mut ev_collision_enter: EventReader<CollisionEnterEvent>,
-    mut mover_q: Query<&mut OverPlanksCounter, With<Mover>>,
-    planks_q: Query<&Planks, Without<Mover>>,
+pub fn on_mover_pressure_plate_collide(
+    In((_, pressure_plate_entity)): In<(Entity, Entity)>,
+    mut pressure_plate_q: Query<&mut PressurePlate>,
+    mut door_q: Query<&mut Door>,
+    mut planks_q: Query<&mut Planks>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
 ) {
-    for e in ev_collision_enter.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
+    if let Ok(mut pressure_plate) = pressure_plate_q.get_mut(pressure_plate_entity) {
+        pressure_plate.pressed_count += 1;
+        if pressure_plate.pressed_count == 1 {
+            toggle_pressure_plate_targets(&pressure_plate, &mut door_q, &mut planks_q);
+            commands.spawn(audio_server.click.create_one_shot());
+        }
+    }
+}
 
-        if let (Ok(mut over_planks_counter), Ok(planks)) =
-            (mover_q.get_mut(entity1), planks_q.get(entity2))
-        {
-            if planks.active {
-                over_planks_counter.0 += 1;
-            }
+pub fn on_mover_pressure_plate_uncollide(
+    In((_, pressure_plate_entity)): In<(Entity, Entity)>,
+    mut pressure_plate_q: Query<&mut PressurePlate>,
+    mut door_q: Query<&mut Door>,
+    mut planks_q: Query<&mut Planks>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
+) {
+    if let Ok(mut pressure_plate) = pressure_plate_q.get_mut(pressure_plate_entity) {
+        if pressure_plate.pressed_count == 0 {
+            return;
+        }
+        pressure_plate.pressed_count -= 1;
+        if pressure_plate.pressed_count == 0 {
+            toggle_pressure_plate_targets(&pressure_plate, &mut door_q, &mut planks_q);
+            commands.spawn(audio_server.click.create_one_shot());
         }
     }
 }
 
+pub fn on_mover_pit_collide(
+    In((mover_entity, pit_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut OverPits, With<Mover>>,
+) {
+    if let Ok(mut over_pits) = mover_q.get_mut(mover_entity) {
+        over_pits.0.insert(pit_entity);
+    }
+}
+
+pub fn on_mover_pit_uncollide(
+    In((mover_entity, pit_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut OverPits, With<Mover>>,
+) {
+    if let Ok(mut over_pits) = mover_q.get_mut(mover_entity) {
+        over_pits.0.remove(&pit_entity);
+    }
+}
+
+// planks are tracked whether they're active or not, see `OverPlanks`
+pub fn on_mover_planks_collide(
+    In((mover_entity, planks_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut OverPlanks, With<Mover>>,
+) {
+    if let Ok(mut over_planks) = mover_q.get_mut(mover_entity) {
+        over_planks.0.insert(planks_entity);
+    }
+}
+
 pub fn on_mover_planks_uncollide(
-    mut ev_collision_exit: EventReader<CollisionExitEvent>,
-    mut mover_q: Query<&mut OverPlanksCounter, With<Mover>>,
-    planks_q: Query<&Planks, Without<Mover>>,
+    In((mover_entity, planks_entity)): In<(Entity, Entity)>,
+    mut mover_q: Query<&mut OverPlanks, With<Mover>>,
+    mut planks_q: Query<&mut Planks>,
+    audio_server: Res<AudioServer>,
+    mut commands: Commands,
 ) {
-    for e in ev_collision_exit.read() {
-        let (entity1, entity2) = align_entities(e.0, e.1, &mover_q);
+    if let Ok(mut over_planks) = mover_q.get_mut(mover_entity) {
+        over_planks.0.remove(&planks_entity);
 
-        if let (Ok(mut over_planks_counter), Ok(_)) =
-            (mover_q.get_mut(entity1), planks_q.get(entity2))
-        {
-            if over_planks_counter.0 > 0 {
-                over_planks_counter.0 -= 1;
+        // walking off crumbling planks wears them down, once they give way
+        // anyone still on them falls
+        if let Ok(mut planks) = planks_q.get_mut(planks_entity) {
+            if planks.cross() {
+                commands.spawn(audio_server.click.create_one_shot());
             }
         }
     }
 }
 
-// Helpers
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_utils::HashSet;
+
+    fn collider(layers: &str) -> Collider {
+        return Collider {
+            shape: CollisionShape::Circle { radius: 4.0 },
+            offset: Vec2::ZERO,
+            name: String::new(),
+            colliding_with: HashSet::new(),
+            active: true,
+            layers: parse_layers(layers),
+            mask: ALL_LAYERS,
+        };
+    }
+
+    fn spawn_mover(world: &mut World) -> Entity {
+        return world
+            .spawn((
+                Mover {
+                    dir: IVec2::X,
+                    target: IVec2::X,
+                    coord: IVec2::ZERO,
+                    move_percent: 0.0,
+                    clockwise: true,
+                    speed: 1.0,
+                    current_speed: 1.0,
+                    acceleration: 0.0,
+                    easing: Easing::Linear,
+                    steering: String::new(),
+                    seed: 0,
+                    steps: 0,
+                },
+                KeyRing::default(),
+                Transform::default(),
+                Sprite::default(),
+                collider("mover"),
+            ))
+            .id();
+    }
+
+    fn plate_app() -> (App, Entity, Entity, Entity) {
+        let mut app = App::new();
+        app.add_event::<CollisionEnterEvent>()
+            .add_event::<CollisionExitEvent>()
+            .add_event::<MoverKilled>()
+            .init_resource::<AudioServer>()
+            .add_plugins(CollisionEventsPlugin);
+
+        let door = app
+            .world
+            .spawn(Door {
+                id: 0,
+                coord: IVec2::ZERO,
+                open: false,
+                key_id: 0,
+            })
+            .id();
+        let planks = app
+            .world
+            .spawn((
+                Planks {
+                    id: 0,
+                    active: false,
+                    kind: PlanksKind::Toggle,
+                    default_active: false,
+                    duration: 1.0,
+                    remaining: 0.0,
+                    crossings_left: 1,
+                    crumbled: false,
+                },
+                // out of everyone's reach
+                Transform::from_xyz(1000.0, 0.0, 0.0),
+                collider("platform"),
+            ))
+            .id();
+        let pressure_plate = app
+            .world
+            .spawn((
+                PressurePlate {
+                    target_ids: Vec::new(),
+                    targets: vec![door, planks],
+                    pressed_count: 0,
+                },
+                Transform::default(),
+                collider("trigger"),
+            ))
+            .id();
+        return (app, door, planks, pressure_plate);
+    }
+
+    #[test]
+    fn movers_that_die_on_a_pressure_plate_release_it() {
+        let (mut app, door, planks, pressure_plate) = plate_app();
+        let mover = spawn_mover(&mut app.world);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((update_colliders, dispatch_collision_rules, movers_die).chain());
+
+        schedule.run(&mut app.world);
+        assert!(app.world.get::<Door>(door).unwrap().open);
+        assert!(app.world.get::<Planks>(planks).unwrap().active);
+
+        app.world.send_event(MoverKilled {
+            victim: mover,
+            cause: KillCause::Trap,
+            killer: None,
+        });
+        schedule.run(&mut app.world);
+        assert!(app.world.get::<Dead>(mover).is_some());
+
+        schedule.run(&mut app.world);
+        assert!(!app.world.get::<Door>(door).unwrap().open);
+        assert!(!app.world.get::<Planks>(planks).unwrap().active);
+        assert_eq!(
+            app.world
+                .get::<PressurePlate>(pressure_plate)
+                .unwrap()
+                .pressed_count,
+            0
+        );
+    }
+
+    #[test]
+    fn pressure_plates_leave_crumbled_planks_down() {
+        let (mut app, _, planks, _) = plate_app();
+        {
+            let mut planks = app.world.get_mut::<Planks>(planks).unwrap();
+            planks.kind = PlanksKind::Crumbling;
+            planks.crumbled = true;
+        }
+        spawn_mover(&mut app.world);
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems((update_colliders, dispatch_collision_rules).chain());
+        schedule.run(&mut app.world);
 
-fn align_entities<T, F>(e1: Entity, e2: Entity, query_first: &Query<T, F>) -> (Entity, Entity)
-where
-    T: QueryData,
-    F: QueryFilter,
-{
-    if let Ok(_) = query_first.get(e1) {
-        return (e1, e2);
+        assert!(!app.world.get::<Planks>(planks).unwrap().active);
     }
-    return (e2, e1);
 }
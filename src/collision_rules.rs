@@ -0,0 +1,128 @@
+use bevy::{
+    ecs::{event::ManualEventReader, system::SystemId},
+    prelude::*,
+};
+
+use crate::*;
+
+// Plugin
+
+pub struct CollisionRulesPlugin;
+impl Plugin for CollisionRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollisionRules>().add_systems(
+            FixedUpdate,
+            dispatch_collision_rules
+                .after(update_colliders)
+                .run_if(in_state(MapLoadState::Done)),
+        );
+    }
+}
+
+// Resources
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPhase {
+    Enter,
+    Exit,
+}
+
+struct CollisionRule {
+    phase: CollisionPhase,
+    matches_first: fn(&World, Entity) -> bool,
+    matches_second: fn(&World, Entity) -> bool,
+    action: SystemId<(Entity, Entity)>,
+}
+
+/// Every collision interaction in the game. For each collision event, rules
+/// run in the order they were registered, and all exits are handled before
+/// any enters.
+#[derive(Resource, Default)]
+pub struct CollisionRules {
+    rules: Vec<CollisionRule>,
+}
+
+pub trait CollisionRulesAppExt {
+    /// Runs `action` with `(a, b)` whenever an entity with `A` and an entity
+    /// with `B` start (or stop) colliding.
+    fn add_collision_rule<A: Component, B: Component, M>(
+        &mut self,
+        phase: CollisionPhase,
+        action: impl IntoSystem<(Entity, Entity), (), M> + 'static,
+    ) -> &mut Self;
+}
+
+impl CollisionRulesAppExt for App {
+    fn add_collision_rule<A: Component, B: Component, M>(
+        &mut self,
+        phase: CollisionPhase,
+        action: impl IntoSystem<(Entity, Entity), (), M> + 'static,
+    ) -> &mut Self {
+        let action = self.world.register_system(action);
+        self.world
+            .get_resource_or_insert_with(CollisionRules::default)
+            .rules
+            .push(CollisionRule {
+                phase,
+                matches_first: has_component::<A>,
+                matches_second: has_component::<B>,
+                action,
+            });
+        return self;
+    }
+}
+
+// Systems
+
+pub fn dispatch_collision_rules(
+    world: &mut World,
+    mut enter_reader: Local<ManualEventReader<CollisionEnterEvent>>,
+    mut exit_reader: Local<ManualEventReader<CollisionExitEvent>>,
+) {
+    let exits: Vec<(Entity, Entity)> = exit_reader
+        .read(world.resource::<Events<CollisionExitEvent>>())
+        .map(|e| (e.0, e.1))
+        .collect();
+    let enters: Vec<(Entity, Entity)> = enter_reader
+        .read(world.resource::<Events<CollisionEnterEvent>>())
+        .map(|e| (e.0, e.1))
+        .collect();
+
+    world.resource_scope(|world, rules: Mut<CollisionRules>| {
+        for (phase, pairs) in [
+            (CollisionPhase::Exit, exits),
+            (CollisionPhase::Enter, enters),
+        ] {
+            for (entity1, entity2) in pairs {
+                for rule in rules.rules.iter().filter(|r| r.phase == phase) {
+                    // an earlier rule may have despawned one of them
+                    if world.get_entity(entity1).is_none() || world.get_entity(entity2).is_none() {
+                        break;
+                    }
+
+                    let pair = if (rule.matches_first)(world, entity1)
+                        && (rule.matches_second)(world, entity2)
+                    {
+                        (entity1, entity2)
+                    } else if (rule.matches_first)(world, entity2)
+                        && (rule.matches_second)(world, entity1)
+                    {
+                        (entity2, entity1)
+                    } else {
+                        continue;
+                    };
+
+                    if let Err(err) = world.run_system_with_input(rule.action, pair) {
+                        println!("collision rule failed to run: {:?}", err);
+                    }
+                }
+            }
+        }
+    });
+}
+
+// Helpers
+
+fn has_component<T: Component>(world: &World, entity: Entity) -> bool {
+    return world.get::<T>(entity).is_some();
+}
@@ -1,5 +1,9 @@
 use crate::*;
-use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+};
+use bevy_utils::{HashMap, HashSet};
 use std::{f32::consts::PI, fmt::Pointer};
 
 // Constants
@@ -16,17 +20,42 @@ pub const MAX_LIVES: u16 = 3;
 
 // Plugin
 
-pub struct DeathPlugin;
+pub struct DeathPlugin {
+    pub lives_rule: LivesRule,
+}
 impl Plugin for DeathPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, movers_die)
+        app.add_event::<MoverKilled>()
+            .init_resource::<KillStats>()
+            .init_resource::<RespawnDelay>()
+            .add_systems(
+                FixedUpdate,
+                filter_mover_kills
+                    .after(dispatch_collision_rules)
+                    .before(movers_die),
+            )
+            .add_systems(
+                FixedUpdate,
+                (movers_die, count_kills).after(filter_mover_kills),
+            )
             .add_systems(
                 Update,
-                dead_adventurers_respawn()
+                dead_adventurers_respawn
                     .run_if(in_state(PauseState::Running))
+                    .run_if(in_state(RewindState::Idle))
                     .run_if(in_state(MapLoadState::Done)),
             )
-            .insert_resource(Lives(MAX_LIVES));
+            .add_systems(Startup, add_hydrators)
+            .add_systems(Update, (apply_lives_rule, update_lives_label).chain())
+            .add_systems(
+                OnEnter(SceneState::Transitioning),
+                (
+                    refill_lives.run_if(in_state(MapLoadState::Done)),
+                    reset_respawn_delay,
+                ),
+            )
+            .insert_resource(self.lives_rule)
+            .insert_resource(Lives(self.lives_rule.max_lives()));
     }
 }
 
@@ -35,125 +64,254 @@ impl Plugin for DeathPlugin {
 #[derive(Debug, Resource)]
 pub struct Lives(pub u16);
 
+/// How lives are handed out, the number is how many deaths are forgiven
+/// before the game is over. Set by `DeathPlugin`, a map can override it with
+/// a `LivesRule` object.
+#[derive(Debug, Clone, Copy, PartialEq, Resource, Component)]
+pub enum LivesRule {
+    /// Refilled whenever a new level starts
+    PerLevel(u16),
+    /// Refilled only when going back to the title or retrying after a game over
+    PerRun(u16),
+    Infinite,
+}
+
+impl LivesRule {
+    pub fn from_name(name: &str, max_lives: u16) -> Option<Self> {
+        return match name {
+            "per_level" => Some(LivesRule::PerLevel(max_lives)),
+            "per_run" => Some(LivesRule::PerRun(max_lives)),
+            "infinite" => Some(LivesRule::Infinite),
+            _ => None,
+        };
+    }
+
+    pub fn max_lives(&self) -> u16 {
+        return match self {
+            LivesRule::PerLevel(max_lives) | LivesRule::PerRun(max_lives) => *max_lives,
+            LivesRule::Infinite => 0,
+        };
+    }
+}
+
+/// How many movers died of each cause over the whole run.
+#[derive(Debug, Default, Resource)]
+pub struct KillStats(pub HashMap<KillCause, u32>);
+
+/// Seconds left before a dead adventurer restarts the level
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct RespawnDelay(pub f32);
+
+impl Default for RespawnDelay {
+    fn default() -> Self {
+        return RespawnDelay(DEATH_DELAY);
+    }
+}
+
+#[derive(SystemParam)]
+struct LivesTracker<'w> {
+    lives: ResMut<'w, Lives>,
+    lives_rule: Res<'w, LivesRule>,
+    map_server: ResMut<'w, MapServer>,
+    game_over: ResMut<'w, GameOver>,
+}
+
+impl<'w> LivesTracker<'w> {
+    fn lose_life(&mut self) {
+        if *self.lives_rule == LivesRule::Infinite {
+            // just retry
+        } else if self.lives.0 > 0 {
+            self.lives.0 -= 1;
+        } else {
+            self.game_over.level_idx = self.map_server.map_idx;
+            self.map_server.go_to_map(GAME_OVER_MAP);
+        }
+    }
+}
+
+// Events
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KillCause {
+    /// Lost a fight between an adventurer and a goblinoid
+    Goblinoid,
+    Pit,
+    ExitWithoutTreasure,
+    Trap,
+    /// Required treasure fell into a pit, so the level can't be finished
+    TreasureLost,
+}
+
+/// Raised whenever something kills a mover. Kills of movers that are already
+/// dead are dropped before any subscriber sees them, so each mover dies once.
+#[derive(Debug, Clone, Event)]
+pub struct MoverKilled {
+    pub victim: Entity,
+    pub cause: KillCause,
+    /// The mover that did the killing, if any
+    pub killer: Option<Entity>,
+}
+
 // Components
 
-#[derive(Debug, Component)]
-pub struct Dead {
-    pub killed_by: Option<Entity>,
-    pub fell_into_pit: bool,
+/// Marks a mover that has died, `MoverKilled` says what killed it
+#[derive(Debug, Clone, Component)]
+pub struct Dead;
+
+type DyingMover = (
+    &'static mut Transform,
+    &'static mut Sprite,
+    &'static mut Collider,
+    Option<(&'static mut MoverAnimations, &'static mut SpriteAnimation)>,
+);
+
+// Hydrators
+
+fn hydrate_lives_rule(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let name =
+        get_property_value_from_object_or_default_s(object_data, "lives_rule", "per_run".into());
+    let max_lives =
+        get_property_value_from_object_or_default_i(object_data, "max_lives", MAX_LIVES as i64);
+
+    match LivesRule::from_name(&name, max_lives as u16) {
+        Some(lives_rule) => {
+            entity_commands.insert(lives_rule);
+        }
+        None => println!("unknown lives rule:{}", name),
+    }
 }
 
 // Systems
 
-fn movers_die(
-    mut dead_mover_q: Query<
-        (Entity, &mut Transform, &mut Sprite, &mut Collider, &Dead),
-        With<Mover>,
-    >,
-    mut treasure_train_q: Query<(Entity, &mut TreasureTrain)>,
-    mut treasure_collider_q: Query<&mut Collider, (With<Treasure>, Without<Mover>)>,
-    mut commands: Commands,
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators.register_hydrator("LivesRule", hydrate_lives_rule);
+}
+
+fn apply_lives_rule(
+    lives_rule_q: Query<&LivesRule, Added<LivesRule>>,
+    mut lives_rule: ResMut<LivesRule>,
+    mut lives: ResMut<Lives>,
 ) {
-    for (mover_entity, mut transform, mut sprite, mut collider, dead) in dead_mover_q.iter_mut() {
-        let mut did_treasure_transfer = false;
-
-        // When killed by a mover
-        if let Some(killed_by) = dead.killed_by {
-            let mut killed_train_e: Option<Entity> = None;
-            let mut killed_train: Option<Mut<'_, TreasureTrain>> = None;
-            let mut killer_train: Option<Mut<'_, TreasureTrain>> = None;
-
-            for (entity, treasure_train) in treasure_train_q.iter_mut() {
-                if treasure_train.mover == mover_entity {
-                    killed_train = Some(treasure_train);
-                    killed_train_e = Some(entity);
-                } else if treasure_train.mover == killed_by {
-                    killer_train = Some(treasure_train);
-                }
-            }
+    for new_lives_rule in lives_rule_q.iter() {
+        if *lives_rule != *new_lives_rule {
+            *lives_rule = *new_lives_rule;
+            lives.0 = lives_rule.max_lives();
+        }
+    }
+}
 
-            if let (Some(killed_train_e), Some(killed_train), Some(mut killer_train)) =
-                (killed_train_e, killed_train, killer_train)
-            {
-                for treasure in &killed_train.treasures {
-                    killer_train.treasures.push(*treasure);
-                }
+pub fn filter_mover_kills(
+    mut ev_mover_killed: ResMut<Events<MoverKilled>>,
+    alive_q: Query<(), (With<Mover>, Without<Dead>)>,
+) {
+    let mut killed = HashSet::<Entity>::new();
+    let kills: Vec<MoverKilled> = ev_mover_killed
+        .drain()
+        .filter(|kill| alive_q.contains(kill.victim) && killed.insert(kill.victim))
+        .collect();
 
-                // despawn the treasure train
-                commands.entity(killed_train_e).despawn();
+    for kill in kills {
+        ev_mover_killed.send(kill);
+    }
+}
 
-                did_treasure_transfer = true;
-            }
-        }
+pub fn movers_die(
+    mut ev_mover_killed: EventReader<MoverKilled>,
+    mut mover_q: Query<DyingMover, With<Mover>>,
+    mut commands: Commands,
+) {
+    for kill in ev_mover_killed.read() {
+        if let Ok((mut transform, mut sprite, mut collider, animations)) =
+            mover_q.get_mut(kill.victim)
+        {
+            commands.entity(kill.victim).remove::<Mover>().insert(Dead);
+            collider.active = false;
+            transform.translation.z -= DEATH_OFFSET;
 
-        if !did_treasure_transfer {
-            for (entity, treasure_train) in treasure_train_q.iter() {
-                if treasure_train.mover == mover_entity {
-                    // re enable colliders for all treasures on the train
-                    for &treasure_entity in &treasure_train.treasures {
-                        if let Ok(mut treasure_collider) =
-                            treasure_collider_q.get_mut(treasure_entity)
-                        {
-                            treasure_collider.active = true;
-                        }
-                    }
-                    // despawn the treasure train
-                    commands.entity(entity).despawn();
-                    break;
+            let death_animation = if kill.cause == KillCause::Pit {
+                MoverAnimation::Fall
+            } else {
+                MoverAnimation::Killed
+            };
+            if let Some((mut mover_animations, mut sprite_animation)) = animations {
+                if mover_animations.play(death_animation, &mut sprite_animation) {
+                    continue;
                 }
             }
+
+            // no animation, so squash the sprite instead
+            if kill.cause == KillCause::Pit {
+                transform.scale = transform.scale * PIT_DEATH_SCALE;
+            } else {
+                transform.scale = transform.scale * DEATH_SCALE;
+            }
+            transform.rotate_z(deg_to_rad(DEATH_ROTATION));
+            sprite.color = DEATH_COLOR;
         }
+    }
+}
 
-        commands.entity(mover_entity).remove::<Mover>();
-        collider.active = false;
+fn count_kills(mut ev_mover_killed: EventReader<MoverKilled>, mut kill_stats: ResMut<KillStats>) {
+    for kill in ev_mover_killed.read() {
+        *kill_stats.0.entry(kill.cause).or_insert(0) += 1;
+    }
+}
 
-        // When killed by pit
-        if dead.fell_into_pit {
-            transform.scale = transform.scale * PIT_DEATH_SCALE;
-        } else {
-            transform.scale = transform.scale * DEATH_SCALE;
+fn dead_adventurers_respawn(
+    dead_mover_q: Query<Entity, (With<Adventurer>, With<Dead>)>,
+    time: Res<Time>,
+    mut respawn_delay: ResMut<RespawnDelay>,
+    mut next_state: ResMut<NextState<SceneState>>,
+    mut lives_tracker: LivesTracker,
+) {
+    for _ in dead_mover_q.iter() {
+        respawn_delay.0 -= time.delta_seconds();
+        if respawn_delay.0 <= 0.0 {
+            respawn_delay.0 = DEATH_DELAY;
+            lives_tracker.lose_life();
+            next_state.set(SceneState::Transitioning);
         }
-        transform.rotate_z(deg_to_rad(DEATH_ROTATION));
-        transform.translation.z -= DEATH_OFFSET;
-        sprite.color = DEATH_COLOR;
     }
 }
 
-fn dead_adventurers_respawn() -> impl FnMut(
-    Query<Entity, (With<Adventurer>, With<Dead>)>,
-    Res<Time>,
-    ResMut<NextState<SceneState>>,
-    ResMut<Lives>,
-    ResMut<MapServer>,
-    Query<&mut Text, With<LivesLabel>>,
+fn reset_respawn_delay(mut respawn_delay: ResMut<RespawnDelay>) {
+    respawn_delay.0 = DEATH_DELAY;
+}
+
+fn refill_lives(
+    mut lives: ResMut<Lives>,
+    lives_rule: Res<LivesRule>,
+    map_server: Res<MapServer>,
+    mut last_map_idx: Local<usize>,
 ) {
-    let mut death_delay = DEATH_DELAY;
-
-    return move |dead_mover_q,
-                 time,
-                 mut next_state,
-                 mut lives,
-                 mut map_server,
-                 mut lives_label_q| {
-        for _ in dead_mover_q.iter() {
-            death_delay -= time.delta_seconds();
-            if death_delay <= 0.0 {
-                death_delay = DEATH_DELAY;
-                if lives.0 > 0 {
-                    lives.0 -= 1;
-                    next_state.set(SceneState::Transitioning);
-                } else {
-                    lives.0 = MAX_LIVES;
-                    map_server.go_to_first_map();
-                    next_state.set(SceneState::Transitioning);
-                }
+    let new_level = map_server.map_idx != *last_map_idx;
+    *last_map_idx = map_server.map_idx;
 
-                if let Ok(mut lives_label) = lives_label_q.get_single_mut() {
-                    lives_label.sections[1].value = lives.0.to_string();
-                }
-            }
-        }
+    let refill = match *lives_rule {
+        LivesRule::PerLevel(_) => new_level,
+        LivesRule::PerRun(_) => map_server.map_idx == 0,
+        LivesRule::Infinite => false,
     };
+    if refill {
+        lives.0 = lives_rule.max_lives();
+    }
+}
+
+fn update_lives_label(
+    lives: Res<Lives>,
+    lives_rule: Res<LivesRule>,
+    mut lives_label_q: Query<&mut Text, With<LivesLabel>>,
+) {
+    if let Ok(mut lives_label) = lives_label_q.get_single_mut() {
+        let value = if *lives_rule == LivesRule::Infinite {
+            "-".to_string()
+        } else {
+            lives.0.to_string()
+        };
+        if lives_label.sections[1].value != value {
+            lives_label.sections[1].value = value;
+        }
+    }
 }
 
 // Helpers
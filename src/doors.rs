@@ -0,0 +1,141 @@
+use crate::*;
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+// Plugin
+
+pub struct DoorsPlugin;
+impl Plugin for DoorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_hydrators).add_systems(
+            FixedPostUpdate,
+            (hide_open_doors, initialize_pressure_plates),
+        );
+    }
+}
+
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators
+        .register_hydrator("Door", hydrate_door)
+        .register_hydrator("Key", hydrate_key)
+        .register_hydrator("PressurePlate", hydrate_pressure_plate);
+}
+
+// Components
+
+#[derive(Debug, Component)]
+pub struct Door {
+    pub id: u16,
+    pub coord: IVec2,
+    pub open: bool,
+    // object id of the key that opens this door, 0 if only pressure plates can
+    pub key_id: i64,
+}
+
+#[derive(Debug, Component)]
+pub struct Key {
+    pub id: u16,
+}
+
+#[derive(Debug, Clone, Component, Default)]
+pub struct KeyRing {
+    pub keys: Vec<Entity>,
+    pub key_ids: Vec<i64>,
+}
+
+#[derive(Debug, Component)]
+pub struct PressurePlate {
+    pub target_ids: Vec<i64>,
+    pub targets: Vec<Entity>,
+    pub pressed_count: u16,
+}
+
+// Hydrators
+
+fn hydrate_door(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let open = get_property_value_from_object_or_default_b(object_data, "open", false);
+    let key_id = get_property_value_from_object_or_default_i(object_data, "key", 0);
+
+    entity_commands.insert(Door {
+        id: object_data.id,
+        coord: IVec2::new(object_data.x as i32, object_data.y as i32),
+        open,
+        key_id,
+    });
+}
+
+fn hydrate_key(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    entity_commands.insert(Key { id: object_data.id });
+}
+
+fn hydrate_pressure_plate(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let mut target_ids = Vec::<i64>::new();
+    let mut idx = 1;
+
+    loop {
+        let target_name = format!("{}{}", "target_", idx);
+        let target_id = get_property_value_from_object_or_default_i(object_data, &target_name, -1);
+        if target_id == -1 {
+            break;
+        }
+        target_ids.push(target_id);
+        idx += 1;
+    }
+
+    entity_commands.insert((
+        PressurePlate {
+            target_ids,
+            targets: Vec::new(),
+            pressed_count: 0,
+        },
+        Uninintialized,
+    ));
+}
+
+// Systems
+
+fn hide_open_doors(mut door_q: Query<(&mut Sprite, &Door)>) {
+    for (mut sprite, door) in door_q.iter_mut() {
+        sprite.color = if door.open { Color::NONE } else { Color::WHITE };
+    }
+}
+
+fn initialize_pressure_plates(
+    door_q: Query<(Entity, &Door)>,
+    planks_q: Query<(Entity, &Planks)>,
+    mut pressure_plate_q: Query<(Entity, &mut PressurePlate), With<Uninintialized>>,
+    mut commands: Commands,
+) {
+    for (pressure_plate_entity, mut pressure_plate) in pressure_plate_q.iter_mut() {
+        for target_id in pressure_plate.target_ids.clone() {
+            if let Some((door_entity, _)) = door_q.iter().find(|(_, d)| d.id == target_id as u16) {
+                pressure_plate.targets.push(door_entity);
+            } else if let Some((planks_entity, _)) =
+                planks_q.iter().find(|(_, p)| p.id == target_id as u16)
+            {
+                pressure_plate.targets.push(planks_entity);
+            }
+        }
+
+        commands
+            .entity(pressure_plate_entity)
+            .remove::<Uninintialized>();
+    }
+}
+
+// Helpers
+
+/// Flips every door and planks a pressure plate is linked to.
+pub fn toggle_pressure_plate_targets(
+    pressure_plate: &PressurePlate,
+    door_q: &mut Query<&mut Door>,
+    planks_q: &mut Query<&mut Planks>,
+) {
+    for &target in &pressure_plate.targets {
+        if let Ok(mut door) = door_q.get_mut(target) {
+            door.open = !door.open;
+        } else if let Ok(mut planks) = planks_q.get_mut(target) {
+            let active = !planks.active;
+            planks.set_active(active);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{map_loader::ObjectProperty, *};
+
+// Constants
+
+const WALKABLE_TILE: u8 = 1;
+const FLOOR_PROPERTY: &str = "floor";
+
+// Data
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloorTile {
+    Wall,
+    Floor,
+    /// Pushes a mover one tile in the given direction
+    Conveyor(IVec2),
+    /// Keeps a mover sliding forward until it's blocked
+    Ice,
+    /// Can't be entered while moving against the given direction
+    OneWay(IVec2),
+    /// Forces a mover to turn toward the given direction
+    Arrow(IVec2),
+}
+
+// Helpers
+
+pub fn floor_tile_from_coord(coord: IVec2, map_data: &MapData) -> FloorTile {
+    let tile = tile_data_from_coord(coord, map_data);
+    if tile == 0 {
+        return FloorTile::Wall;
+    }
+
+    // tileset ids are 0 based, map data is 1 based
+    let properties = match map_data.tile_properties.get(&(tile as u32 - 1)) {
+        Some(properties) => properties,
+        None => {
+            return if tile == WALKABLE_TILE {
+                FloorTile::Floor
+            } else {
+                FloorTile::Wall
+            };
+        }
+    };
+
+    let dir = IVec2::new(
+        get_tile_property_i(properties, "dir_x") as i32,
+        get_tile_property_i(properties, "dir_y") as i32,
+    );
+
+    return match properties.iter().find(|p| p.name == FLOOR_PROPERTY) {
+        Some(prop) => match prop.value_s.as_str() {
+            "floor" => FloorTile::Floor,
+            "conveyor" => FloorTile::Conveyor(dir),
+            "ice" => FloorTile::Ice,
+            "one_way" => FloorTile::OneWay(dir),
+            "arrow" => FloorTile::Arrow(dir),
+            val => {
+                println!("unknown floor type:{}", val);
+                FloorTile::Wall
+            }
+        },
+        None if tile == WALKABLE_TILE => FloorTile::Floor,
+        None => FloorTile::Wall,
+    };
+}
+
+/// Whether a mover standing on `coord` can step toward `dir`.
+pub fn can_enter(coord: IVec2, dir: IVec2, map_data: &MapData) -> bool {
+    if dir == IVec2::ZERO {
+        return false;
+    }
+    return match floor_tile_from_coord(coord + dir, map_data) {
+        FloorTile::Wall => false,
+        FloorTile::OneWay(one_way_dir) => dir != -one_way_dir,
+        _ => true,
+    };
+}
+
+/// Direction the floor under a mover forces it to take, if any. The bool is
+/// whether the mover should also turn to face that direction.
+pub fn forced_dir(ctx: &SteeringContext) -> Option<(IVec2, bool)> {
+    return match floor_tile_from_coord(ctx.coord, ctx.map) {
+        FloorTile::Conveyor(push_dir) if ctx.is_walkable(push_dir) => Some((push_dir, false)),
+        FloorTile::Ice if ctx.is_walkable(ctx.dir) => Some((ctx.dir, true)),
+        FloorTile::Arrow(arrow_dir) if ctx.is_walkable(arrow_dir) => Some((arrow_dir, true)),
+        _ => None,
+    };
+}
+
+fn get_tile_property_i(properties: &[ObjectProperty], property_name: &str) -> i64 {
+    return match properties.iter().find(|p| p.name == property_name) {
+        Some(prop) => prop.value_i,
+        None => 0,
+    };
+}
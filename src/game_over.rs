@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{ComponentHydrators, MapLoadState, Uninintialized};
+
+// Constants
+
+pub const GAME_OVER_MAP: &str = "gameover";
+
+// Plugin
+
+pub struct GameOverPlugin;
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameOver>()
+            .add_systems(Startup, add_hydrators)
+            .add_systems(
+                Update,
+                show_level_reached.run_if(in_state(MapLoadState::Done)),
+            );
+    }
+}
+
+// Resources
+
+#[derive(Debug, Default, Resource)]
+pub struct GameOver {
+    // map the adventurer ran out of lives on
+    pub level_idx: usize,
+}
+
+// Components
+
+#[derive(Debug, Component, Default)]
+pub struct LevelReachedLabel;
+
+// Systems
+
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators.register_tag::<(LevelReachedLabel, Uninintialized)>("LevelReachedLabel");
+}
+
+fn show_level_reached(
+    mut label_q: Query<&mut Text, Added<LevelReachedLabel>>,
+    game_over: Res<GameOver>,
+) {
+    for mut label in label_q.iter_mut() {
+        if label.sections.len() > 1 {
+            label.sections[1].value = game_over.level_idx.to_string();
+        }
+    }
+}
@@ -1,9 +1,15 @@
+mod actions;
 mod audio_server;
 mod brmap;
 mod clickable_area;
+mod clickable_focus;
 mod collision;
 mod collision_events;
+mod collision_rules;
 mod death;
+mod doors;
+mod floor_tiles;
+mod game_over;
 mod helpers;
 mod hydrate_components;
 mod map_loader;
@@ -11,30 +17,44 @@ mod movement;
 mod pause;
 mod pits_and_planks;
 mod pixel_perfect_camera;
+mod rewind;
 mod scene;
+mod score;
+mod sprite_animation;
+mod steering;
 mod teleporter;
+mod treasure_drop;
 mod treasure_train;
 mod ui;
-mod you_win;
 
+use actions::*;
 use audio_server::*;
 use bevy::{asset::{load_internal_binary_asset, AssetMetaCheck}, prelude::*};
 use brmap::*;
 use clickable_area::*;
+use clickable_focus::*;
 use collision::*;
 use collision_events::*;
+use collision_rules::*;
 use death::*;
+use doors::*;
+use floor_tiles::*;
+use game_over::*;
 use helpers::*;
 use hydrate_components::*;
 use movement::*;
 use pause::*;
 use pits_and_planks::*;
 use pixel_perfect_camera::*;
+use rewind::*;
 use scene::*;
+use score::*;
+use sprite_animation::*;
+use steering::*;
 use teleporter::*;
+use treasure_drop::*;
 use treasure_train::*;
 use ui::*;
-use you_win::*;
 
 fn main() {
     let mut app = App::new();
@@ -58,10 +78,12 @@ fn main() {
                 String::from("maps/tutorial/3.tmx"),
                 String::from("maps/tutorial/4.tmx"),
                 String::from("maps/tutorial/youwin.tmx"),
+                String::from("maps/tutorial/gameover.tmx"),
             ]),
             CollisionPlugin {
                 debug_collisions: false,
             },
+            CollisionRulesPlugin,
             CollisionEventsPlugin,
             AudioServerPlugin,
             TreasureTrainPlugin,
@@ -70,10 +92,24 @@ fn main() {
             ClickableAreaPlugin { debug_clicks: true },
             TeleporterPlugin,
             PitsAndPlanksPlugin,
-            DeathPlugin,
+            DeathPlugin {
+                lives_rule: LivesRule::PerRun(MAX_LIVES),
+            },
             PausePlugin,
         )) // Yo, you can only have so many plugins per call to add_plugins
-        .add_plugins((UIPlugin, PixelPerfectCameraPlugin, YouWinPlugin));
+        .add_plugins((
+            UIPlugin,
+            PixelPerfectCameraPlugin,
+            ActionsPlugin,
+            RewindPlugin,
+            SteeringPlugin,
+            DoorsPlugin,
+            GameOverPlugin,
+            SpriteAnimationPlugin,
+            ScorePlugin,
+            TreasureDropPlugin,
+            ClickableFocusPlugin,
+        ));
 
     // This needs to happen after `DefaultPlugins` is added.
     load_internal_binary_asset!(
@@ -1,6 +1,6 @@
 use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
 use bevy::prelude::*;
-use bevy_utils::BoxedFuture;
+use bevy_utils::{BoxedFuture, HashMap};
 use roxmltree::*;
 use serde::{Deserialize, Serialize};
 use std::io::Error;
@@ -27,6 +27,8 @@ pub struct ObjectReference {
     pub template: Handle<TemplateData>,
     pub x: u16,
     pub y: u16,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
     pub obj_type: String,
     pub properties: Vec<ObjectProperty>,
 }
@@ -46,12 +48,25 @@ pub struct SpritesheetData {
     pub tile_width: u8,
     pub columns: u32,
     pub sprite: Handle<Image>,
+    pub tile_properties: HashMap<u32, Vec<ObjectProperty>>,
+    pub tile_animations: HashMap<u32, Vec<AnimationFrame>>,
+    // tile animations whose tile has an `animation` property, keyed by its value
+    pub animations: HashMap<String, Vec<AnimationFrame>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    pub tile_id: u32,
+    // seconds
+    pub duration: f32,
 }
 
 #[derive(Asset, TypePath, Debug)]
 pub struct TemplateData {
     pub sprite_sheet: Handle<SpritesheetData>,
     pub sprite_idx: u32,
+    pub width: f64,
+    pub height: f64,
     pub properties: Vec<ObjectProperty>,
 }
 #[derive(Debug, Clone)]
@@ -221,6 +236,12 @@ impl AssetLoader for MapLoader {
                         tile_width,
                         h,
                     ),
+                    width: object_elm
+                        .attribute("width")
+                        .map(|w| str::parse::<f64>(w).expect("can't convert width into f64")),
+                    height: object_elm
+                        .attribute("height")
+                        .map(|h| str::parse::<f64>(h).expect("can't convert height into f64")),
                     properties,
                     obj_type: String::from(obj_type),
                 });
@@ -314,9 +335,72 @@ impl AssetLoader for SpriteSheetLoader {
                 .attribute("source")
                 .expect("can't find image source");
 
+            // Snag per-tile Properties
+            let mut tile_properties = HashMap::<u32, Vec<ObjectProperty>>::new();
+            let mut tile_animations = HashMap::<u32, Vec<AnimationFrame>>::new();
+            for tile_elm in tileset_elm
+                .children()
+                .filter(|n| n.tag_name() == "tile".into())
+            {
+                let tile_id = str::parse::<u32>(tile_elm.attribute("id").expect("can't find id"))
+                    .expect("can't parse tile id");
+
+                let properties_elm = tile_elm
+                    .children()
+                    .find(|n| n.tag_name() == "properties".into());
+                if let Some(elm) = properties_elm {
+                    let mut properties = Vec::<ObjectProperty>::new();
+                    for property_elm in elm.children() {
+                        if !property_elm.is_element() {
+                            continue;
+                        }
+
+                        properties.push(object_property_from_property_element(property_elm));
+                    }
+                    tile_properties.insert(tile_id, properties);
+                }
+
+                let animation_elm = tile_elm
+                    .children()
+                    .find(|n| n.tag_name() == "animation".into());
+                if let Some(elm) = animation_elm {
+                    let mut frames = Vec::<AnimationFrame>::new();
+                    for frame_elm in elm.children().filter(|n| n.tag_name() == "frame".into()) {
+                        frames.push(AnimationFrame {
+                            tile_id: str::parse::<u32>(
+                                frame_elm.attribute("tileid").expect("can't find tileid"),
+                            )
+                            .expect("can't parse frame tileid"),
+                            duration: str::parse::<f32>(
+                                frame_elm
+                                    .attribute("duration")
+                                    .expect("can't find duration"),
+                            )
+                            .expect("can't parse frame duration")
+                                / 1000.0,
+                        });
+                    }
+                    tile_animations.insert(tile_id, frames);
+                }
+            }
+
+            // Snag named Animations
+            let mut animations = HashMap::<String, Vec<AnimationFrame>>::new();
+            for (tile_id, frames) in tile_animations.iter() {
+                let name_property = tile_properties
+                    .get(tile_id)
+                    .and_then(|properties| properties.iter().find(|p| p.name == "animation"));
+                if let Some(name_property) = name_property {
+                    animations.insert(name_property.value_s.clone(), frames.clone());
+                }
+            }
+
             return Ok(SpritesheetData {
                 tile_width,
                 columns,
+                tile_properties,
+                tile_animations,
+                animations,
                 sprite: load_context.load(local_path_to_project_path(
                     source,
                     &load_context.asset_path().to_string(),
@@ -372,15 +456,21 @@ impl AssetLoader for TemplateLoader {
                 &load_context.asset_path().to_string(),
             );
 
+            let object_elm = doc
+                .descendants()
+                .find(|n| n.tag_name() == "object".into())
+                .expect("can't find object");
+
             // Snag Sprite Index
-            let sprite_idx = str::parse::<u32>(
-                doc.descendants()
-                    .find(|n| n.tag_name() == "object".into())
-                    .expect("can't find object")
-                    .attribute("gid")
-                    .expect("can't find gid"),
-            )
-            .expect("can't parse gid");
+            let sprite_idx =
+                str::parse::<u32>(object_elm.attribute("gid").expect("can't find gid"))
+                    .expect("can't parse gid");
+
+            // Snag Size
+            let width = str::parse::<f64>(object_elm.attribute("width").unwrap_or("16"))
+                .expect("can't parse width");
+            let height = str::parse::<f64>(object_elm.attribute("height").unwrap_or("16"))
+                .expect("can't parse height");
 
             // Snag Properties
 
@@ -405,6 +495,8 @@ impl AssetLoader for TemplateLoader {
             return Ok(TemplateData {
                 sprite_sheet: load_context.load(sprite_sheet_path),
                 sprite_idx,
+                width,
+                height,
                 properties,
             });
         });
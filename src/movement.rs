@@ -1,5 +1,6 @@
 use crate::*;
 use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_utils::HashMap;
 use std::f32::consts::PI;
 
 // Constants
@@ -9,7 +10,10 @@ const LEFT: IVec2 = IVec2::new(-1, 0);
 const UP: IVec2 = IVec2::new(0, 1);
 const DOWN: IVec2 = IVec2::new(0, -1);
 
-const MOVER_SPEED: f32 = 1.95;
+pub const MOVER_SPEED: f32 = 1.95;
+// Colliders are checked after every fixed tick, and at 64hz this moves an
+// eighth of a tile (2px) per tick, well under the smallest collider
+const MAX_MOVER_SPEED: f32 = 8.0;
 
 // Plugin
 
@@ -20,7 +24,8 @@ impl Plugin for MovementPlugin {
             FixedUpdate,
             move_movers
                 .run_if(in_state(MapLoadState::Done))
-                .run_if(in_state(PauseState::Running)),
+                .run_if(in_state(PauseState::Running))
+                .run_if(in_state(RewindState::Idle)),
         );
     }
 }
@@ -31,19 +36,70 @@ fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
 
 // Components
 
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Component)]
 pub struct Mover {
     pub dir: IVec2,
     pub target: IVec2,
     pub coord: IVec2,
     pub move_percent: f32,
     pub clockwise: bool,
+    pub speed: f32,
+    pub current_speed: f32,
+    pub acceleration: f32,
+    pub easing: Easing,
+    pub steering: String,
+    pub seed: u64,
+    pub steps: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    Sine,
+    Smooth,
+}
+impl Easing {
+    pub fn from_name(name: &str) -> Self {
+        return match name {
+            "linear" => Easing::Linear,
+            "smooth" => Easing::Smooth,
+            _ => Easing::Sine,
+        };
+    }
+
+    pub fn apply(&self, percent: f32) -> f32 {
+        return match self {
+            Easing::Linear => percent,
+            Easing::Sine => (percent * PI * 0.5).sin(),
+            Easing::Smooth => percent * percent * (3.0 - 2.0 * percent),
+        };
+    }
 }
 
 pub fn hydrate_mover(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
     let x = get_property_value_from_object_or_default_i(object_data, "dir_x", 0);
     let y = get_property_value_from_object_or_default_i(object_data, "dir_y", 0);
     let clockwise = get_property_value_from_object_or_default_b(object_data, "clockwise", false);
+    let speed = clamp(
+        get_property_value_from_object_or_default_f(object_data, "speed", MOVER_SPEED as f64)
+            as f32,
+        0.0,
+        MAX_MOVER_SPEED,
+    );
+    let acceleration =
+        get_property_value_from_object_or_default_f(object_data, "acceleration", 0.0) as f32;
+    let easing = Easing::from_name(&get_property_value_from_object_or_default_s(
+        object_data,
+        "easing",
+        "sine".into(),
+    ));
+    let steering = get_property_value_from_object_or_default_s(
+        object_data,
+        "steering",
+        DEFAULT_STEERING_RULE.into(),
+    );
+    let seed =
+        get_property_value_from_object_or_default_i(object_data, "seed", object_data.id as i64);
 
     entity_commands.insert((
         Mover {
@@ -55,24 +111,56 @@ pub fn hydrate_mover(entity_commands: &mut EntityCommands, object_data: &ObjectD
             coord: IVec2::new(object_data.x as i32, object_data.y as i32),
             move_percent: 0.0,
             clockwise,
+            speed,
+            // movers without acceleration start at full speed
+            current_speed: if acceleration > 0.0 { 0.0 } else { speed },
+            acceleration,
+            easing,
+            steering,
+            seed: seed as u64,
+            steps: 0,
         },
-        OverPlanksCounter(0),
-        OverPitCounter(0),
+        KeyRing::default(),
     ));
+    entity_commands.add(validate_steering);
 }
 
 // Systems
 
 pub fn move_movers(
-    mut movers: Query<(&mut Transform, &mut Mover)>,
+    mut movers: Query<(&mut Transform, &mut Mover, Has<Adventurer>, &KeyRing)>,
+    door_q: Query<&Door>,
     time: Res<Time>,
     map_server: Res<MapServer>,
+    steering_rules: Res<SteeringRules>,
 ) {
     let active_map = &map_server.maps[map_server.map_idx];
 
-    for (mut transform, mut mover) in movers.iter_mut() {
+    let adventurer_coords: Vec<IVec2> = movers
+        .iter()
+        .filter(|(_, _, is_adventurer, _)| *is_adventurer)
+        .map(|(_, mover, _, _)| mover.target)
+        .collect();
+
+    let closed_doors: HashMap<IVec2, i64> = door_q
+        .iter()
+        .filter(|door| !door.open)
+        .map(|door| (door.coord, door.key_id))
+        .collect();
+
+    for (mut transform, mut mover, _, key_ring) in movers.iter_mut() {
+        if mover.acceleration > 0.0 {
+            mover.current_speed = clamp(
+                mover.current_speed + mover.acceleration * time.delta_seconds(),
+                0.0,
+                mover.speed,
+            );
+        } else {
+            mover.current_speed = mover.speed;
+        }
+
         mover.move_percent = clamp(
-            mover.move_percent + MOVER_SPEED * time.delta_seconds(),
+            mover.move_percent + mover.current_speed * time.delta_seconds(),
             0.0,
             1.0,
         );
@@ -86,38 +174,49 @@ pub fn move_movers(
             coord_to_pos(mover.coord.y as f32),
             transform.translation.z,
         );
-        transform.translation = cerp_v3(previous_position, destination, mover.move_percent);
+        transform.translation = lerp_v3(
+            previous_position,
+            destination,
+            mover.easing.apply(mover.move_percent),
+        );
 
         if mover.move_percent == 1.0 {
             mover.move_percent = 0.0;
             mover.coord = mover.target;
 
-            let forward = mover.dir;
-            let side = rotate_dir(mover.dir, mover.clockwise);
-            let back = -mover.dir;
-            let target: IVec2;
-
-            if tile_data_from_coord(mover.coord + forward, active_map) == 1 {
-                target = mover.coord + forward;
-            } else if tile_data_from_coord(mover.coord + side, active_map) == 1 {
-                target = mover.coord + side;
-                mover.dir = side;
-            } else if tile_data_from_coord(mover.coord + back, active_map) == 1 {
-                target = mover.coord + back;
-                mover.dir = back;
-            } else {
-                target = mover.coord;
-                mover.dir = IVec2::ZERO;
+            mover.steps += 1;
+
+            let ctx = SteeringContext {
+                coord: mover.coord,
+                dir: mover.dir,
+                clockwise: mover.clockwise,
+                seed: mover.seed,
+                steps: mover.steps,
+                adventurer_coords: &adventurer_coords,
+                closed_doors: &closed_doors,
+                key_ids: &key_ring.key_ids,
+                map: active_map,
+            };
+
+            if let Some((dir, turn)) = forced_dir(&ctx) {
+                if turn {
+                    mover.dir = dir;
+                }
+                mover.target = mover.coord + dir;
+                continue;
             }
 
-            mover.target = target;
+            let steer = steering_rules.get_rule(&mover.steering);
+            let dir = steer(&ctx);
+            mover.dir = dir;
+            mover.target = mover.coord + dir;
         }
     }
 }
 
 // Helpers
 
-fn rotate_dir(dir: IVec2, cw: bool) -> IVec2 {
+pub fn rotate_dir(dir: IVec2, cw: bool) -> IVec2 {
     if dir == RIGHT {
         return if cw { DOWN } else { UP };
     } else if dir == DOWN {
@@ -130,10 +229,6 @@ fn rotate_dir(dir: IVec2, cw: bool) -> IVec2 {
     return IVec2::ZERO;
 }
 
-fn cerp_v3(start: Vec3, end: Vec3, percent: f32) -> Vec3 {
-    return lerp_v3(start, end, (percent * PI * 0.5).sin());
-}
-
 fn lerp_v3(start: Vec3, end: Vec3, percent: f32) -> Vec3 {
     return start * (1.0 - percent) + end * percent;
 }
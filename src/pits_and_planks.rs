@@ -1,5 +1,17 @@
-use crate::*;
+use crate::{map_loader::AnimationFrame, *};
 use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_utils::{HashMap, HashSet};
+
+// Constants
+
+// every look planks can have, see `Planks::animation_name`
+const PLANKS_ANIMATIONS: [&str; 5] = [
+    "planks_toggle",
+    "planks_crumbling",
+    "planks_crumbling_worn",
+    "planks_timed",
+    "planks_timed_ending",
+];
 
 // Plugin
 
@@ -9,18 +21,121 @@ impl Plugin for PitsAndPlanksPlugin {
         app.add_systems(Startup, add_hydrators)
             .add_systems(
                 FixedPostUpdate,
-                (hide_inactive_planks, initialize_planks_triggers),
+                (
+                    hide_inactive_planks,
+                    animate_planks,
+                    initialize_planks_triggers,
+                    disable_used_planks_triggers,
+                ),
+            )
+            .add_systems(
+                FixedPreUpdate,
+                toggle_planks_triggers.run_if(in_state(RewindState::Idle)),
             )
-            .add_systems(FixedPreUpdate, toggle_planks_triggers);
+            .add_systems(
+                Update,
+                preview_planks_triggers.run_if(in_state(MapLoadState::Done)),
+            )
+            .add_systems(FixedUpdate, initialize_movers.before(update_colliders))
+            .add_systems(
+                FixedUpdate,
+                (
+                    revert_timed_planks.run_if(in_state(PauseState::Running)),
+                    movers_fall_into_pits,
+                )
+                    .chain()
+                    .after(dispatch_collision_rules)
+                    .before(filter_mover_kills)
+                    .run_if(in_state(RewindState::Idle)),
+            );
     }
 }
 
 // Components
 
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlanksKind {
+    /// Stays however its trigger left it
+    Toggle,
+    /// Gives way for good once enough movers have walked off it
+    Crumbling,
+    /// Goes back to how it started a while after being triggered
+    Timed,
+}
+
+impl PlanksKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "toggle" => Some(PlanksKind::Toggle),
+            "crumbling" => Some(PlanksKind::Crumbling),
+            "timed" => Some(PlanksKind::Timed),
+            _ => None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Component)]
 pub struct Planks {
     pub id: u16,
     pub active: bool,
+    pub kind: PlanksKind,
+    // what timed planks revert to
+    pub default_active: bool,
+    // seconds timed planks stay triggered
+    pub duration: f32,
+    pub remaining: f32,
+    // movers crumbling planks can still carry
+    pub crossings_left: u16,
+    pub crumbled: bool,
+}
+
+impl Planks {
+    pub fn set_active(&mut self, active: bool) {
+        if self.crumbled {
+            return;
+        }
+        self.active = active;
+        if self.kind == PlanksKind::Timed {
+            self.remaining = if active != self.default_active {
+                self.duration
+            } else {
+                0.0
+            };
+        }
+    }
+
+    /// A mover walked off, returns true if that was the last one it could carry.
+    pub fn cross(&mut self) -> bool {
+        if self.kind != PlanksKind::Crumbling || !self.active || self.crumbled {
+            return false;
+        }
+        self.crossings_left = self.crossings_left.saturating_sub(1);
+        if self.crossings_left == 0 {
+            self.active = false;
+            self.crumbled = true;
+        }
+        return self.crumbled;
+    }
+
+    fn animation_name(&self) -> &'static str {
+        return match self.kind {
+            PlanksKind::Toggle => "planks_toggle",
+            PlanksKind::Crumbling if self.crossings_left <= 1 => "planks_crumbling_worn",
+            PlanksKind::Crumbling => "planks_crumbling",
+            // blink for the last second
+            PlanksKind::Timed if self.remaining > 0.0 && self.remaining < 1.0 => {
+                "planks_timed_ending"
+            }
+            PlanksKind::Timed => "planks_timed",
+        };
+    }
+}
+
+/// The tileset animations planks switch between as their state changes.
+#[derive(Debug, Component)]
+pub struct PlanksAnimations {
+    animations: HashMap<&'static str, Vec<AnimationFrame>>,
+    playing: Option<&'static str>,
 }
 
 #[derive(Debug, Component)]
@@ -28,25 +143,59 @@ pub struct PlanksTrigger {
     pub planks: Vec<Entity>,
     pub planks_ids: Vec<i64>,
     pub active_planks_idx: usize,
+    // can only be clicked once
+    pub one_use: bool,
+    pub used: bool,
 }
 
 #[derive(Debug, Component, Default)]
 pub struct Pit;
 
-#[derive(Debug, Component)]
-pub struct OverPlanksCounter(pub u16);
+/// Every planks a mover overlaps, active or not. Whether it's supported is
+/// worked out from these each time, so planks toggled under it count right away.
+#[derive(Debug, Clone, Component, Default)]
+pub struct OverPlanks(pub HashSet<Entity>);
 
-#[derive(Debug, Component)]
-pub struct OverPitCounter(pub u16);
+#[derive(Debug, Clone, Component, Default)]
+pub struct OverPits(pub HashSet<Entity>);
 
 // Hydrators
 
 fn hydrate_planks(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
     let active = get_property_value_from_object_or_default_b(object_data, "active", true);
+    let kind_name =
+        get_property_value_from_object_or_default_s(object_data, "kind", "toggle".into());
+    let kind = PlanksKind::from_name(&kind_name).unwrap_or_else(|| {
+        println!("unknown planks kind:{}, using toggle", kind_name);
+        PlanksKind::Toggle
+    });
+    let duration = get_property_value_from_object_or_default_f(object_data, "duration", 3.0);
+    let crossings = get_property_value_from_object_or_default_i(object_data, "crossings", 1);
+
+    let mut animations = HashMap::<&'static str, Vec<AnimationFrame>>::new();
+    for name in PLANKS_ANIMATIONS {
+        match object_data.sprite_sheet.animations.get(name) {
+            Some(frames) => {
+                animations.insert(name, frames.clone());
+            }
+            None => println!("no tile animation named:{}", name),
+        }
+    }
+
     entity_commands.insert((
+        PlanksAnimations {
+            animations,
+            playing: None,
+        },
         Planks {
             id: object_data.id,
             active,
+            kind,
+            default_active: active,
+            duration: duration as f32,
+            remaining: 0.0,
+            crossings_left: crossings.max(1) as u16,
+            crumbled: false,
         },
         Uninintialized,
     ));
@@ -66,11 +215,15 @@ fn hydrate_planks_trigger(entity_commands: &mut EntityCommands, object_data: &Ob
         idx += 1;
     }
 
+    let one_use = get_property_value_from_object_or_default_b(object_data, "one_use", false);
+
     entity_commands.insert((
         PlanksTrigger {
             planks: Vec::new(),
             planks_ids,
             active_planks_idx: 0,
+            one_use,
+            used: false,
         },
         Uninintialized,
     ));
@@ -87,11 +240,39 @@ fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
 
 fn hide_inactive_planks(mut planks_q: Query<(&mut Sprite, &Planks)>) {
     for (mut planks_sprite, planks) in planks_q.iter_mut() {
-        planks_sprite.color = if planks.active {
+        let color = if planks.active {
             Color::WHITE
         } else {
             Color::NONE
         };
+        if planks_sprite.color != color {
+            planks_sprite.color = color;
+        }
+    }
+}
+
+fn animate_planks(mut planks_q: Query<(&Planks, &mut PlanksAnimations, &mut SpriteAnimation)>) {
+    for (planks, mut planks_animations, mut sprite_animation) in planks_q.iter_mut() {
+        let name = planks.animation_name();
+        if planks_animations.playing == Some(name) {
+            continue;
+        }
+        if let Some(frames) = planks_animations.animations.get(name) {
+            sprite_animation.play(frames, true);
+        }
+        planks_animations.playing = Some(name);
+    }
+}
+
+fn disable_used_planks_triggers(
+    mut planks_trigger_q: Query<(&PlanksTrigger, &mut ClickableArea, &mut Sprite)>,
+) {
+    for (planks_trigger, mut clickable_area, mut sprite) in planks_trigger_q.iter_mut() {
+        let enabled = !(planks_trigger.one_use && planks_trigger.used);
+        if clickable_area.enabled != enabled {
+            clickable_area.enabled = enabled;
+            sprite.color = if enabled { Color::WHITE } else { Color::GRAY };
+        }
     }
 }
 
@@ -116,39 +297,53 @@ fn initialize_planks_triggers(
     }
 }
 
+fn initialize_movers(
+    mover_q: Query<Entity, (Added<Mover>, Without<OverPlanks>)>,
+    mut commands: Commands,
+) {
+    for mover_entity in mover_q.iter() {
+        commands
+            .entity(mover_entity)
+            .insert((OverPlanks::default(), OverPits::default()));
+    }
+}
+
 fn toggle_planks_triggers(
-    mut ev_mouse_click: EventReader<MouseClickEvent>,
+    mut ev_activate: EventReader<ActivateEvent>,
     mut planks_trigger_q: Query<&mut PlanksTrigger, With<ClickableArea>>,
-    mut planks_q: Query<(&mut Planks, &mut Collider)>,
+    mut planks_q: Query<&mut Planks>,
     audio_server: Res<AudioServer>,
     mut commands: Commands,
 ) {
-    for e in ev_mouse_click.read() {
+    for e in ev_activate.read() {
         let entity = e.0;
         if let Ok(mut planks_trigger) = planks_trigger_q.get_mut(entity) {
+            if planks_trigger.one_use && planks_trigger.used {
+                continue;
+            }
+            planks_trigger.used = true;
+
             if planks_trigger.planks.len() == 1 {
-                if let Ok((mut planks, mut collider)) =
+                if let Ok(mut planks) =
                     planks_q.get_mut(planks_trigger.planks[planks_trigger.active_planks_idx])
                 {
-                    planks.active = !planks.active;
-                    collider.active = planks.active;
+                    let active = !planks.active;
+                    planks.set_active(active);
                 }
             } else {
-                if let Ok((mut planks, mut collider)) =
+                if let Ok(mut planks) =
                     planks_q.get_mut(planks_trigger.planks[planks_trigger.active_planks_idx])
                 {
-                    planks.active = false;
-                    collider.active = false;
+                    planks.set_active(false);
                 }
 
                 planks_trigger.active_planks_idx =
                     (planks_trigger.active_planks_idx + 1) % planks_trigger.planks.len();
 
-                if let Ok((mut planks, mut collider)) =
+                if let Ok(mut planks) =
                     planks_q.get_mut(planks_trigger.planks[planks_trigger.active_planks_idx])
                 {
-                    planks.active = true;
-                    collider.active = true;
+                    planks.set_active(true);
                 }
 
                 commands.spawn(audio_server.click.create_one_shot());
@@ -157,33 +352,211 @@ fn toggle_planks_triggers(
     }
 }
 
+// outlines the planks the hovered trigger would bring in or take away
+fn preview_planks_triggers(
+    hovered: Res<HoveredArea>,
+    focus: Res<ClickableFocus>,
+    planks_trigger_q: Query<&PlanksTrigger>,
+    planks_q: Query<(&Transform, &Planks)>,
+    mut gizmos: Gizmos,
+) {
+    let previewed = hovered.previewed(&focus);
+    if let Some(planks_trigger) = previewed.and_then(|entity| planks_trigger_q.get(entity).ok()) {
+        if planks_trigger.planks.is_empty() {
+            return;
+        }
+
+        let current = planks_trigger.planks[planks_trigger.active_planks_idx];
+        let next = planks_trigger.planks
+            [(planks_trigger.active_planks_idx + 1) % planks_trigger.planks.len()];
+
+        for planks_entity in [current, next] {
+            if let Ok((transform, planks)) = planks_q.get(planks_entity) {
+                if planks.crumbled {
+                    continue;
+                }
+                // a lone planks toggles, otherwise the next one takes over
+                let appears = if current == next {
+                    !planks.active
+                } else {
+                    planks_entity == next
+                };
+                let color = if appears {
+                    PREVIEW_ADD_COLOR
+                } else {
+                    PREVIEW_REMOVE_COLOR
+                };
+                draw_outline(
+                    &mut gizmos,
+                    transform.translation.truncate(),
+                    &CollisionShape::FullTile,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+fn revert_timed_planks(mut planks_q: Query<&mut Planks>, time: Res<Time>) {
+    for mut planks in planks_q.iter_mut() {
+        if planks.kind != PlanksKind::Timed || planks.remaining <= 0.0 {
+            continue;
+        }
+        planks.remaining -= time.delta_seconds();
+        if planks.remaining <= 0.0 {
+            let active = planks.default_active;
+            planks.set_active(active);
+        }
+    }
+}
+
 pub fn movers_fall_into_pits(
-    mover_q: Query<
-        (
-            Entity,
-            &OverPlanksCounter,
-            &OverPitCounter,
-            Option<&Adventurer>,
-        ),
-        With<Mover>,
-    >,
-    audio_server: Res<AudioServer>,
-    mut commands: Commands,
+    mover_q: Query<(Entity, &OverPlanks, &OverPits), With<Mover>>,
+    planks_q: Query<&Planks>,
+    mut ev_mover_killed: EventWriter<MoverKilled>,
 ) {
-    for (entity, over_planks_counter, over_pit_counter, adventurer) in mover_q.iter() {
-        if over_planks_counter.0 > 0 {
+    for (entity, over_planks, over_pits) in mover_q.iter() {
+        if over_pits.0.is_empty() {
             continue;
         }
-        if over_pit_counter.0 > 0 {
-            if let Some(_) = adventurer {
-                commands.spawn(audio_server.die.create_one_shot());
-            } else {
-                commands.spawn(audio_server.kill.create_one_shot());
-            }
-            commands.entity(entity).insert(Dead {
-                killed_by: None,
-                fell_into_pit: true,
-            });
+        // only planks that are active right now hold a mover up
+        if planks_q
+            .iter_many(&over_planks.0)
+            .any(|planks| planks.active)
+        {
+            continue;
         }
+        ev_mover_killed.send(MoverKilled {
+            victim: entity,
+            cause: KillCause::Pit,
+            killer: None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn spawn_planks(world: &mut World, active: bool, kind: PlanksKind) -> Entity {
+        return world
+            .spawn(Planks {
+                id: 0,
+                active,
+                kind,
+                default_active: active,
+                duration: 1.0,
+                remaining: 0.0,
+                crossings_left: 1,
+                crumbled: false,
+            })
+            .id();
+    }
+
+    fn spawn_mover(world: &mut World, planks: &[Entity], pits: &[Entity]) -> Entity {
+        return world
+            .spawn((
+                Mover {
+                    dir: IVec2::X,
+                    target: IVec2::X,
+                    coord: IVec2::ZERO,
+                    move_percent: 0.5,
+                    clockwise: true,
+                    speed: 1.0,
+                    current_speed: 1.0,
+                    acceleration: 0.0,
+                    easing: Easing::Linear,
+                    steering: String::new(),
+                    seed: 0,
+                    steps: 0,
+                },
+                OverPlanks(planks.iter().copied().collect()),
+                OverPits(pits.iter().copied().collect()),
+            ))
+            .id();
+    }
+
+    fn world_with_pit() -> (World, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Events<MoverKilled>>();
+        let pit = world.spawn(Pit).id();
+        return (world, pit);
+    }
+
+    fn fallen(world: &mut World) -> Vec<Entity> {
+        world.run_system_once(movers_fall_into_pits);
+        return world
+            .resource_mut::<Events<MoverKilled>>()
+            .drain()
+            .map(|kill| kill.victim)
+            .collect();
+    }
+
+    fn set_active(world: &mut World, planks: Entity, active: bool) {
+        world.get_mut::<Planks>(planks).unwrap().set_active(active);
+    }
+
+    #[test]
+    fn toggling_planks_off_mid_crossing_drops_the_mover() {
+        let (mut world, pit) = world_with_pit();
+        let planks = spawn_planks(&mut world, true, PlanksKind::Toggle);
+        let mover = spawn_mover(&mut world, &[planks], &[pit]);
+        assert!(fallen(&mut world).is_empty());
+
+        set_active(&mut world, planks, false);
+        assert_eq!(fallen(&mut world), vec![mover]);
+    }
+
+    #[test]
+    fn toggling_planks_off_and_on_mid_crossing_keeps_the_mover_up() {
+        let (mut world, pit) = world_with_pit();
+        let planks = spawn_planks(&mut world, true, PlanksKind::Toggle);
+        spawn_mover(&mut world, &[planks], &[pit]);
+
+        set_active(&mut world, planks, false);
+        set_active(&mut world, planks, true);
+        assert!(fallen(&mut world).is_empty());
+
+        // and again, nothing was counted twice
+        set_active(&mut world, planks, false);
+        set_active(&mut world, planks, true);
+        assert!(fallen(&mut world).is_empty());
+    }
+
+    #[test]
+    fn planks_toggled_on_under_a_mover_hold_it_up() {
+        let (mut world, pit) = world_with_pit();
+        let planks = spawn_planks(&mut world, false, PlanksKind::Toggle);
+        spawn_mover(&mut world, &[planks], &[pit]);
+
+        set_active(&mut world, planks, true);
+        assert!(fallen(&mut world).is_empty());
+    }
+
+    #[test]
+    fn other_active_planks_keep_holding_the_mover_up() {
+        let (mut world, pit) = world_with_pit();
+        let behind = spawn_planks(&mut world, true, PlanksKind::Toggle);
+        let ahead = spawn_planks(&mut world, true, PlanksKind::Toggle);
+        let mover = spawn_mover(&mut world, &[behind, ahead], &[pit]);
+
+        set_active(&mut world, behind, false);
+        assert!(fallen(&mut world).is_empty());
+
+        set_active(&mut world, ahead, false);
+        assert_eq!(fallen(&mut world), vec![mover]);
+    }
+
+    #[test]
+    fn crumbled_planks_drop_whoever_is_still_on_them() {
+        let (mut world, pit) = world_with_pit();
+        let planks = spawn_planks(&mut world, true, PlanksKind::Crumbling);
+        let mover = spawn_mover(&mut world, &[planks], &[pit]);
+
+        assert!(world.get_mut::<Planks>(planks).unwrap().cross());
+        // crumbled planks can't be toggled back
+        set_active(&mut world, planks, true);
+        assert_eq!(fallen(&mut world), vec![mover]);
     }
 }
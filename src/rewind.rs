@@ -0,0 +1,452 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_utils::HashSet;
+use std::collections::VecDeque;
+
+use crate::*;
+
+// Constants
+
+const REWIND_KEY: KeyCode = KeyCode::KeyR;
+const UNDO_KEY: KeyCode = KeyCode::KeyZ;
+
+/// Number of fixed ticks kept in the history (about 10 seconds at the default 64hz).
+const MAX_HISTORY: usize = 640;
+
+// Plugin
+
+pub struct RewindPlugin;
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<RewindState>()
+            .insert_resource(RewindHistory::default())
+            .add_systems(OnEnter(SceneState::Transitioning), clear_history)
+            .add_systems(
+                Update,
+                handle_rewind_input.run_if(in_state(MapLoadState::Done)),
+            )
+            .add_systems(
+                FixedPostUpdate,
+                (
+                    record_history
+                        .run_if(in_state(PauseState::Running))
+                        .run_if(in_state(RewindState::Idle)),
+                    rewind_history.run_if(in_state(RewindState::Rewinding)),
+                    undo_last_click
+                        .run_if(in_state(PauseState::Running))
+                        .run_if(in_state(RewindState::Idle)),
+                )
+                    .run_if(in_state(MapLoadState::Done))
+                    .run_if(in_state(SceneState::Stable)),
+            );
+    }
+}
+
+// States
+
+#[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RewindState {
+    #[default]
+    Idle,
+    Rewinding,
+}
+
+// Resources
+
+#[derive(Debug, Resource, Default)]
+pub struct RewindHistory {
+    snapshots: VecDeque<GameplaySnapshot>,
+    undo_requested: bool,
+}
+
+// Data
+
+#[derive(Debug, Clone)]
+struct MoverSnapshot {
+    entity: Entity,
+    transform: Transform,
+    color: Color,
+    mover: Option<Mover>,
+    dead: Option<Dead>,
+    over_planks: OverPlanks,
+    over_pits: OverPits,
+    key_ring: KeyRing,
+    portal_cooldown: Option<PortalCooldown>,
+    animation: Option<(SpriteAnimation, usize)>,
+    playing: Option<MoverAnimation>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct GameplaySnapshot {
+    // set when a click happened right after this snapshot was taken
+    checkpoint: bool,
+    movers: Vec<MoverSnapshot>,
+    colliders: Vec<(Entity, bool, HashSet<Entity>)>,
+    planks: Vec<(Entity, Planks)>,
+    planks_triggers: Vec<(Entity, usize, bool)>,
+    exit_portals: Vec<(Entity, bool)>,
+    enter_portals: Vec<(Entity, Option<Entity>)>,
+    teleporters: Vec<(Entity, usize)>,
+    treasures: Vec<(Entity, Transform, Visibility)>,
+    treasure_trains: Vec<TreasureTrain>,
+    doors: Vec<(Entity, bool)>,
+    keys: Vec<(Entity, Visibility)>,
+    pressure_plates: Vec<(Entity, u16)>,
+    respawn_delay: f32,
+    level_score: LevelScore,
+}
+
+type MoverSource = (
+    Entity,
+    &'static Transform,
+    &'static Sprite,
+    Option<&'static Mover>,
+    Option<&'static Dead>,
+    &'static OverPlanks,
+    &'static OverPits,
+    &'static KeyRing,
+    Option<&'static PortalCooldown>,
+    Option<(&'static SpriteAnimation, &'static TextureAtlas)>,
+    Option<&'static MoverAnimations>,
+);
+
+type MoverTarget = (
+    &'static mut Transform,
+    &'static mut Sprite,
+    &'static mut OverPlanks,
+    &'static mut OverPits,
+    &'static mut KeyRing,
+    Option<(&'static mut SpriteAnimation, &'static mut TextureAtlas)>,
+    Option<&'static mut MoverAnimations>,
+);
+
+type TreasureTarget = (&'static mut Transform, &'static mut Visibility);
+type TreasureTargetFilter = (With<Treasure>, Without<OverPits>, Without<Key>);
+
+#[derive(SystemParam)]
+struct SnapshotSources<'w, 's> {
+    mover_q: Query<'w, 's, MoverSource>,
+    collider_q: Query<'w, 's, (Entity, &'static Collider)>,
+    planks_q: Query<'w, 's, (Entity, &'static Planks)>,
+    planks_trigger_q: Query<'w, 's, (Entity, &'static PlanksTrigger)>,
+    exit_portal_q: Query<'w, 's, (Entity, &'static ExitPortal)>,
+    enter_portal_q: Query<'w, 's, (Entity, &'static EnterPortal)>,
+    teleporter_q: Query<'w, 's, (Entity, &'static Teleporter)>,
+    treasure_q: Query<'w, 's, (Entity, &'static Transform, &'static Visibility), With<Treasure>>,
+    treasure_train_q: Query<'w, 's, &'static TreasureTrain>,
+    door_q: Query<'w, 's, (Entity, &'static Door)>,
+    key_q: Query<'w, 's, (Entity, &'static Visibility), With<Key>>,
+    pressure_plate_q: Query<'w, 's, (Entity, &'static PressurePlate)>,
+    respawn_delay: Res<'w, RespawnDelay>,
+    level_score: Res<'w, LevelScore>,
+}
+
+#[derive(SystemParam)]
+struct SnapshotTargets<'w, 's> {
+    mover_q: Query<'w, 's, MoverTarget>,
+    collider_q: Query<'w, 's, &'static mut Collider>,
+    planks_q: Query<'w, 's, &'static mut Planks>,
+    planks_trigger_q: Query<'w, 's, &'static mut PlanksTrigger>,
+    exit_portal_q: Query<'w, 's, &'static mut ExitPortal>,
+    enter_portal_q: Query<'w, 's, &'static mut EnterPortal>,
+    teleporter_q: Query<'w, 's, &'static mut Teleporter>,
+    treasure_q: Query<'w, 's, TreasureTarget, TreasureTargetFilter>,
+    treasure_train_q: Query<'w, 's, Entity, With<TreasureTrain>>,
+    door_q: Query<'w, 's, &'static mut Door>,
+    key_q: Query<'w, 's, &'static mut Visibility, With<Key>>,
+    pressure_plate_q: Query<'w, 's, &'static mut PressurePlate>,
+    respawn_delay: ResMut<'w, RespawnDelay>,
+    level_score: ResMut<'w, LevelScore>,
+    commands: Commands<'w, 's>,
+}
+
+// Systems
+
+fn clear_history(mut history: ResMut<RewindHistory>) {
+    history.snapshots.clear();
+    history.undo_requested = false;
+}
+
+fn handle_rewind_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    rewind_state: Res<State<RewindState>>,
+    mut next_state: ResMut<NextState<RewindState>>,
+    mut history: ResMut<RewindHistory>,
+) {
+    if keys.pressed(REWIND_KEY) {
+        if *rewind_state.get() == RewindState::Idle {
+            next_state.set(RewindState::Rewinding);
+        }
+    } else if *rewind_state.get() == RewindState::Rewinding {
+        next_state.set(RewindState::Idle);
+    }
+
+    if keys.just_pressed(UNDO_KEY) {
+        history.undo_requested = true;
+    }
+}
+
+fn record_history(
+    mut history: ResMut<RewindHistory>,
+    mut ev_activate: EventReader<ActivateEvent>,
+    sources: SnapshotSources,
+) {
+    // the latest snapshot predates any click we haven't seen yet
+    if ev_activate.read().count() > 0 {
+        if let Some(last) = history.snapshots.back_mut() {
+            last.checkpoint = true;
+        }
+    }
+
+    history.snapshots.push_back(take_snapshot(&sources));
+    while history.snapshots.len() > MAX_HISTORY {
+        history.snapshots.pop_front();
+    }
+}
+
+fn rewind_history(mut history: ResMut<RewindHistory>, mut targets: SnapshotTargets) {
+    // always keep the oldest snapshot around so holding rewind stops there
+    let snapshot = if history.snapshots.len() > 1 {
+        history.snapshots.pop_back()
+    } else {
+        history.snapshots.back().cloned()
+    };
+
+    if let Some(snapshot) = snapshot {
+        apply_snapshot(&snapshot, &mut targets);
+    }
+}
+
+fn undo_last_click(mut history: ResMut<RewindHistory>, mut targets: SnapshotTargets) {
+    if !history.undo_requested {
+        return;
+    }
+    history.undo_requested = false;
+
+    if let Some(idx) = history.snapshots.iter().rposition(|s| s.checkpoint) {
+        history.snapshots.truncate(idx + 1);
+
+        // the next undo should go back to the click before this one
+        history.snapshots[idx].checkpoint = false;
+        let snapshot = history.snapshots[idx].clone();
+
+        apply_snapshot(&snapshot, &mut targets);
+    }
+}
+
+// Helpers
+
+fn take_snapshot(sources: &SnapshotSources) -> GameplaySnapshot {
+    return GameplaySnapshot {
+        checkpoint: false,
+        movers: sources
+            .mover_q
+            .iter()
+            .map(
+                |(
+                    entity,
+                    transform,
+                    sprite,
+                    mover,
+                    dead,
+                    over_planks,
+                    over_pits,
+                    key_ring,
+                    portal_cooldown,
+                    animation,
+                    mover_animations,
+                )| {
+                    MoverSnapshot {
+                        entity,
+                        transform: *transform,
+                        color: sprite.color,
+                        mover: mover.cloned(),
+                        dead: dead.cloned(),
+                        over_planks: over_planks.clone(),
+                        over_pits: over_pits.clone(),
+                        key_ring: key_ring.clone(),
+                        portal_cooldown: portal_cooldown.cloned(),
+                        animation: animation.map(|(sprite_animation, texture_atlas)| {
+                            (sprite_animation.clone(), texture_atlas.index)
+                        }),
+                        playing: mover_animations.and_then(|m| m.playing),
+                    }
+                },
+            )
+            .collect(),
+        colliders: sources
+            .collider_q
+            .iter()
+            .map(|(entity, collider)| (entity, collider.active, collider.colliding_with.clone()))
+            .collect(),
+        planks: sources
+            .planks_q
+            .iter()
+            .map(|(entity, planks)| (entity, planks.clone()))
+            .collect(),
+        planks_triggers: sources
+            .planks_trigger_q
+            .iter()
+            .map(|(entity, trigger)| (entity, trigger.active_planks_idx, trigger.used))
+            .collect(),
+        exit_portals: sources
+            .exit_portal_q
+            .iter()
+            .map(|(entity, exit_portal)| (entity, exit_portal.active))
+            .collect(),
+        enter_portals: sources
+            .enter_portal_q
+            .iter()
+            .map(|(entity, enter_portal)| (entity, enter_portal.exit_portal))
+            .collect(),
+        teleporters: sources
+            .teleporter_q
+            .iter()
+            .map(|(entity, teleporter)| (entity, teleporter.active_exit_portal))
+            .collect(),
+        treasures: sources
+            .treasure_q
+            .iter()
+            .map(|(entity, transform, visibility)| (entity, *transform, *visibility))
+            .collect(),
+        treasure_trains: sources.treasure_train_q.iter().cloned().collect(),
+        doors: sources
+            .door_q
+            .iter()
+            .map(|(entity, door)| (entity, door.open))
+            .collect(),
+        keys: sources
+            .key_q
+            .iter()
+            .map(|(entity, visibility)| (entity, *visibility))
+            .collect(),
+        pressure_plates: sources
+            .pressure_plate_q
+            .iter()
+            .map(|(entity, pressure_plate)| (entity, pressure_plate.pressed_count))
+            .collect(),
+        respawn_delay: sources.respawn_delay.0,
+        level_score: sources.level_score.clone(),
+    };
+}
+
+fn apply_snapshot(snapshot: &GameplaySnapshot, targets: &mut SnapshotTargets) {
+    for mover_snapshot in &snapshot.movers {
+        if let Ok((
+            mut transform,
+            mut sprite,
+            mut over_planks,
+            mut over_pits,
+            mut key_ring,
+            animation,
+            mover_animations,
+        )) = targets.mover_q.get_mut(mover_snapshot.entity)
+        {
+            *transform = mover_snapshot.transform;
+            sprite.color = mover_snapshot.color;
+            *over_planks = mover_snapshot.over_planks.clone();
+            *over_pits = mover_snapshot.over_pits.clone();
+            *key_ring = mover_snapshot.key_ring.clone();
+
+            if let (Some((mut sprite_animation, mut texture_atlas)), Some((animation, index))) =
+                (animation, &mover_snapshot.animation)
+            {
+                *sprite_animation = animation.clone();
+                texture_atlas.index = *index;
+            }
+            if let Some(mut mover_animations) = mover_animations {
+                mover_animations.playing = mover_snapshot.playing;
+            }
+        }
+
+        let mut entity_commands = targets.commands.entity(mover_snapshot.entity);
+        match &mover_snapshot.mover {
+            Some(mover) => entity_commands.insert(mover.clone()),
+            None => entity_commands.remove::<Mover>(),
+        };
+        match &mover_snapshot.dead {
+            Some(dead) => entity_commands.insert(dead.clone()),
+            None => entity_commands.remove::<Dead>(),
+        };
+        match &mover_snapshot.portal_cooldown {
+            Some(portal_cooldown) => entity_commands.insert(portal_cooldown.clone()),
+            None => entity_commands.remove::<PortalCooldown>(),
+        };
+    }
+
+    for (entity, active, colliding_with) in &snapshot.colliders {
+        if let Ok(mut collider) = targets.collider_q.get_mut(*entity) {
+            collider.active = *active;
+            collider.colliding_with = colliding_with.clone();
+        }
+    }
+
+    for (entity, planks_snapshot) in &snapshot.planks {
+        if let Ok(mut planks) = targets.planks_q.get_mut(*entity) {
+            *planks = planks_snapshot.clone();
+        }
+    }
+
+    for (entity, active_planks_idx, used) in &snapshot.planks_triggers {
+        if let Ok(mut planks_trigger) = targets.planks_trigger_q.get_mut(*entity) {
+            planks_trigger.active_planks_idx = *active_planks_idx;
+            planks_trigger.used = *used;
+        }
+    }
+
+    for (entity, active) in &snapshot.exit_portals {
+        if let Ok(mut exit_portal) = targets.exit_portal_q.get_mut(*entity) {
+            exit_portal.active = *active;
+        }
+    }
+
+    for (entity, exit_portal) in &snapshot.enter_portals {
+        if let Ok(mut enter_portal) = targets.enter_portal_q.get_mut(*entity) {
+            enter_portal.exit_portal = *exit_portal;
+        }
+    }
+
+    for (entity, active_exit_portal) in &snapshot.teleporters {
+        if let Ok(mut teleporter) = targets.teleporter_q.get_mut(*entity) {
+            teleporter.active_exit_portal = *active_exit_portal;
+        }
+    }
+
+    for (entity, transform, visibility) in &snapshot.treasures {
+        if let Ok((mut treasure_transform, mut treasure_visibility)) =
+            targets.treasure_q.get_mut(*entity)
+        {
+            *treasure_transform = *transform;
+            *treasure_visibility = *visibility;
+        }
+    }
+
+    for (entity, open) in &snapshot.doors {
+        if let Ok(mut door) = targets.door_q.get_mut(*entity) {
+            door.open = *open;
+        }
+    }
+
+    for (entity, visibility) in &snapshot.keys {
+        if let Ok(mut key_visibility) = targets.key_q.get_mut(*entity) {
+            *key_visibility = *visibility;
+        }
+    }
+
+    for (entity, pressed_count) in &snapshot.pressure_plates {
+        if let Ok(mut pressure_plate) = targets.pressure_plate_q.get_mut(*entity) {
+            pressure_plate.pressed_count = *pressed_count;
+        }
+    }
+
+    targets.respawn_delay.0 = snapshot.respawn_delay;
+    // restoring clicks refunds the penalty of every click being undone
+    *targets.level_score = snapshot.level_score.clone();
+
+    // treasure trains come and go, so rebuild them from scratch
+    for entity in targets.treasure_train_q.iter() {
+        targets.commands.entity(entity).despawn();
+    }
+    for treasure_train in &snapshot.treasure_trains {
+        targets.commands.spawn(treasure_train.clone());
+    }
+}
@@ -94,7 +94,7 @@ fn setup_scene(
         let x = idx % map.width;
         let y = map.width - idx / map.width - 1;
 
-        commands.spawn((
+        let mut entity_commands = commands.spawn((
             SpriteBundle {
                 transform: Transform {
                     translation: Vec3 {
@@ -113,6 +113,13 @@ fn setup_scene(
                 index: (map.data[idx] as usize) - 1,
             },
         ));
+
+        let tile_id = map.data[idx] as u32 - 1;
+        if let Some(animation) =
+            SpriteAnimation::from_tile(tile_id, &map.sprite_sheet.tile_animations)
+        {
+            entity_commands.insert(animation);
+        }
     }
 
     for obj in map.objects.iter() {
@@ -145,7 +152,16 @@ fn setup_scene(
         if is_tool {
             entity_commands.insert(Tool);
         } else {
-            entity_commands.insert((sprite_bundle, texture_atlas, PIXEL_PERFECT_LAYERS));
+            // every sprite can animate, idling on its tile's animation if it has one
+            let animation =
+                SpriteAnimation::from_tile(obj.sprite_idx - 1, &obj.sprite_sheet.tile_animations)
+                    .unwrap_or_default();
+            entity_commands.insert((
+                sprite_bundle,
+                texture_atlas,
+                animation,
+                PIXEL_PERFECT_LAYERS,
+            ));
         }
 
         let components_property = obj.properties.iter().find(|prop| prop.name == "Components");
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+// Constants
+
+// bonus for finishing a level right away, shrinks every second
+const TIME_BONUS: f32 = 100.0;
+const TIME_BONUS_PER_SECOND: f32 = 2.0;
+const CLICK_PENALTY: u32 = 5;
+
+// Plugin
+
+pub struct ScorePlugin;
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LevelScore>()
+            .add_systems(Startup, add_hydrators)
+            .add_systems(OnEnter(SceneState::Stable), reset_level_score)
+            .add_systems(
+                Update,
+                (
+                    tick_level_time
+                        .run_if(in_state(PauseState::Running))
+                        .run_if(in_state(RewindState::Idle)),
+                    count_clicks,
+                    count_treasure_value,
+                    update_score_label,
+                )
+                    .chain()
+                    .run_if(in_state(MapLoadState::Done)),
+            );
+    }
+}
+
+// Resources
+
+#[derive(Debug, Clone, Default, Resource)]
+pub struct LevelScore {
+    // value of the treasure the adventurer is carrying
+    pub treasure: u32,
+    pub clicks: u32,
+    // seconds spent on the level
+    pub elapsed: f32,
+}
+
+impl LevelScore {
+    pub fn total(&self) -> u32 {
+        let time_bonus = (TIME_BONUS - self.elapsed * TIME_BONUS_PER_SECOND).max(0.0) as u32;
+        return (self.treasure + time_bonus).saturating_sub(self.clicks * CLICK_PENALTY);
+    }
+}
+
+// Components
+
+#[derive(Debug, Component, Default)]
+pub struct ScoreLabel;
+
+// Systems
+
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators.register_tag::<(ScoreLabel, Uninintialized)>("ScoreLabel");
+}
+
+fn reset_level_score(mut level_score: ResMut<LevelScore>) {
+    *level_score = LevelScore::default();
+}
+
+// only levels with an adventurer are timed
+fn tick_level_time(
+    mut level_score: ResMut<LevelScore>,
+    adventurer_q: Query<(), (With<Adventurer>, With<Mover>)>,
+    time: Res<Time>,
+) {
+    if !adventurer_q.is_empty() {
+        level_score.elapsed += time.delta_seconds();
+    }
+}
+
+fn count_clicks(mut ev_activate: EventReader<ActivateEvent>, mut level_score: ResMut<LevelScore>) {
+    level_score.clicks += ev_activate.read().count() as u32;
+}
+
+fn count_treasure_value(
+    mut level_score: ResMut<LevelScore>,
+    treasure_train_q: Query<&TreasureTrain>,
+    adventurer_q: Query<(), With<Adventurer>>,
+    treasure_q: Query<&Treasure>,
+) {
+    let mut treasure = 0;
+    for treasure_train in treasure_train_q.iter() {
+        if adventurer_q.contains(treasure_train.mover) {
+            treasure += treasure_q
+                .iter_many(&treasure_train.treasures)
+                .map(|t| t.value)
+                .sum::<u32>();
+        }
+    }
+
+    if level_score.treasure != treasure {
+        level_score.treasure = treasure;
+    }
+}
+
+fn update_score_label(
+    level_score: Res<LevelScore>,
+    mut score_label_q: Query<&mut Text, With<ScoreLabel>>,
+) {
+    if let Ok(mut score_label) = score_label_q.get_single_mut() {
+        let value = level_score.total().to_string();
+        if score_label.sections[1].value != value {
+            score_label.sections[1].value = value;
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_utils::HashMap;
+
+use crate::{map_loader::AnimationFrame, *};
+
+// Plugin
+
+pub struct SpriteAnimationPlugin;
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, add_hydrators).add_systems(
+            Update,
+            (play_walk_animations, advance_sprite_animations)
+                .chain()
+                .run_if(in_state(PauseState::Running)),
+        );
+    }
+}
+
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators.register_hydrator("MoverAnimations", hydrate_mover_animations);
+}
+
+// Components
+
+/// Steps a `TextureAtlas` through a list of tileset frames.
+#[derive(Debug, Clone, Component, Default)]
+pub struct SpriteAnimation {
+    frames: Vec<AnimationFrame>,
+    frame_idx: usize,
+    elapsed: f32,
+    looping: bool,
+    pub finished: bool,
+}
+
+impl SpriteAnimation {
+    /// Loops the tileset animation of `tile_id`, if it has one.
+    pub fn from_tile(
+        tile_id: u32,
+        tile_animations: &HashMap<u32, Vec<AnimationFrame>>,
+    ) -> Option<Self> {
+        return tile_animations.get(&tile_id).map(|frames| {
+            let mut animation = SpriteAnimation::default();
+            animation.play(frames, true);
+            animation
+        });
+    }
+
+    pub fn play(&mut self, frames: &[AnimationFrame], looping: bool) {
+        self.frames = frames.to_vec();
+        self.frame_idx = 0;
+        self.elapsed = 0.0;
+        self.looping = looping;
+        self.finished = false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoverAnimation {
+    WalkUp,
+    WalkDown,
+    WalkLeft,
+    WalkRight,
+    Fall,
+    Killed,
+    Teleport,
+}
+
+impl MoverAnimation {
+    pub fn walk_from_dir(dir: IVec2) -> Self {
+        return if dir.x > 0 {
+            MoverAnimation::WalkRight
+        } else if dir.x < 0 {
+            MoverAnimation::WalkLeft
+        } else if dir.y > 0 {
+            MoverAnimation::WalkUp
+        } else {
+            MoverAnimation::WalkDown
+        };
+    }
+
+    fn is_walk(&self) -> bool {
+        return matches!(
+            self,
+            MoverAnimation::WalkUp
+                | MoverAnimation::WalkDown
+                | MoverAnimation::WalkLeft
+                | MoverAnimation::WalkRight
+        );
+    }
+}
+
+/// The tileset animations a mover can play, looked up by the name given in
+/// each tile's `animation` property.
+#[derive(Debug, Clone, Component)]
+pub struct MoverAnimations {
+    animations: HashMap<MoverAnimation, Vec<AnimationFrame>>,
+    pub playing: Option<MoverAnimation>,
+}
+
+impl MoverAnimations {
+    /// Starts `animation` if this mover has frames for it, walks loop and
+    /// everything else plays once.
+    pub fn play(
+        &mut self,
+        animation: MoverAnimation,
+        sprite_animation: &mut SpriteAnimation,
+    ) -> bool {
+        return match self.animations.get(&animation) {
+            Some(frames) => {
+                sprite_animation.play(frames, animation.is_walk());
+                self.playing = Some(animation);
+                true
+            }
+            None => false,
+        };
+    }
+}
+
+// Hydrators
+
+fn hydrate_mover_animations(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let walk_name =
+        get_property_value_from_object_or_default_s(object_data, "walk_animation", String::new());
+
+    let mut names = Vec::<(MoverAnimation, String)>::new();
+    if !walk_name.is_empty() {
+        names.push((MoverAnimation::WalkUp, format!("{}_up", walk_name)));
+        names.push((MoverAnimation::WalkDown, format!("{}_down", walk_name)));
+        names.push((MoverAnimation::WalkLeft, format!("{}_left", walk_name)));
+        names.push((MoverAnimation::WalkRight, format!("{}_right", walk_name)));
+    }
+    for (animation, property_name) in [
+        (MoverAnimation::Fall, "fall_animation"),
+        (MoverAnimation::Killed, "killed_animation"),
+        (MoverAnimation::Teleport, "teleport_animation"),
+    ] {
+        let name =
+            get_property_value_from_object_or_default_s(object_data, property_name, String::new());
+        if !name.is_empty() {
+            names.push((animation, name));
+        }
+    }
+
+    let mut animations = HashMap::<MoverAnimation, Vec<AnimationFrame>>::new();
+    for (animation, name) in names {
+        match object_data.sprite_sheet.animations.get(&name) {
+            Some(frames) => {
+                animations.insert(animation, frames.clone());
+            }
+            None => println!("no tile animation named:{}", name),
+        }
+    }
+
+    entity_commands.insert(MoverAnimations {
+        animations,
+        playing: None,
+    });
+}
+
+// Systems
+
+fn play_walk_animations(mut mover_q: Query<(&Mover, &mut MoverAnimations, &mut SpriteAnimation)>) {
+    for (mover, mut mover_animations, mut sprite_animation) in mover_q.iter_mut() {
+        // let teleporting finish first
+        if mover_animations.playing == Some(MoverAnimation::Teleport) && !sprite_animation.finished
+        {
+            continue;
+        }
+
+        let walk = MoverAnimation::walk_from_dir(mover.dir);
+        if mover_animations.playing != Some(walk) {
+            mover_animations.play(walk, &mut sprite_animation);
+        }
+    }
+}
+
+fn advance_sprite_animations(
+    mut animation_q: Query<(&mut SpriteAnimation, &mut TextureAtlas)>,
+    time: Res<Time>,
+) {
+    for (mut animation, mut texture_atlas) in animation_q.iter_mut() {
+        if animation.frames.is_empty() || animation.finished {
+            continue;
+        }
+
+        animation.elapsed += time.delta_seconds();
+        while animation.frames[animation.frame_idx].duration > 0.0
+            && animation.elapsed >= animation.frames[animation.frame_idx].duration
+        {
+            animation.elapsed -= animation.frames[animation.frame_idx].duration;
+            if animation.frame_idx + 1 < animation.frames.len() {
+                animation.frame_idx += 1;
+            } else if animation.looping {
+                animation.frame_idx = 0;
+            } else {
+                animation.finished = true;
+                break;
+            }
+        }
+
+        let tile_id = animation.frames[animation.frame_idx].tile_id as usize;
+        if texture_atlas.index != tile_id {
+            texture_atlas.index = tile_id;
+        }
+    }
+}
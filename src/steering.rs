@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use bevy_utils::HashMap;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::*;
+
+// Constants
+
+pub const DEFAULT_STEERING_RULE: &str = "forward";
+
+// Plugin
+
+pub struct SteeringPlugin;
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SteeringRules::new())
+            .add_systems(Startup, add_steering_rules);
+    }
+}
+
+fn add_steering_rules(mut steering_rules: ResMut<SteeringRules>) {
+    steering_rules
+        .register_rule(DEFAULT_STEERING_RULE, steer_forward)
+        .register_rule("wall_follow_left", steer_wall_follow_left)
+        .register_rule("wall_follow_right", steer_wall_follow_right)
+        .register_rule("bouncer", steer_bouncer)
+        .register_rule("random", steer_random)
+        .register_rule("chase", steer_chase);
+}
+
+// Resources
+
+/// Picks the next direction for a mover that just arrived on a tile.
+/// Returning `IVec2::ZERO` stops the mover in place.
+pub type SteeringRule = fn(&SteeringContext) -> IVec2;
+
+#[derive(Clone, Debug, Resource)]
+pub struct SteeringRules {
+    rules: HashMap<&'static str, SteeringRule>,
+}
+
+impl SteeringRules {
+    pub fn new() -> Self {
+        return Self {
+            rules: HashMap::new(),
+        };
+    }
+
+    pub fn register_rule(&mut self, rule_name: &'static str, func: SteeringRule) -> &mut Self {
+        self.rules.insert(rule_name, func);
+        return self;
+    }
+
+    pub fn has_rule(&self, rule_name: &str) -> bool {
+        return self.rules.contains_key(rule_name);
+    }
+
+    /// Unknown rules are replaced when the mover is hydrated, see `validate_steering`.
+    pub fn get_rule(&self, rule_name: &str) -> SteeringRule {
+        return match self.rules.get(rule_name) {
+            Some(rule) => *rule,
+            None => steer_forward,
+        };
+    }
+}
+
+/// Falls back to the default rule if a mover asks for one that isn't registered,
+/// queued by `hydrate_mover` so the warning only shows once per mover.
+pub fn validate_steering(entity: Entity, world: &mut World) {
+    let steering = match (
+        world.get::<Mover>(entity),
+        world.get_resource::<SteeringRules>(),
+    ) {
+        (Some(mover), Some(steering_rules)) if !steering_rules.has_rule(&mover.steering) => {
+            mover.steering.clone()
+        }
+        _ => return,
+    };
+
+    println!(
+        "unknown steering rule:{} on {:?}, using {}",
+        steering, entity, DEFAULT_STEERING_RULE
+    );
+    if let Some(mut mover) = world.get_mut::<Mover>(entity) {
+        mover.steering = DEFAULT_STEERING_RULE.into();
+    }
+}
+
+// Data
+
+pub struct SteeringContext<'a> {
+    pub coord: IVec2,
+    pub dir: IVec2,
+    pub clockwise: bool,
+    pub seed: u64,
+    pub steps: u32,
+    pub adventurer_coords: &'a [IVec2],
+    pub closed_doors: &'a HashMap<IVec2, i64>,
+    pub key_ids: &'a [i64],
+    pub map: &'a MapData,
+}
+
+impl<'a> SteeringContext<'a> {
+    pub fn is_walkable(&self, dir: IVec2) -> bool {
+        if !can_enter(self.coord, dir, self.map) {
+            return false;
+        }
+        // closed doors only let through movers carrying their key
+        return match self.closed_doors.get(&(self.coord + dir)) {
+            Some(key_id) => *key_id != 0 && self.key_ids.contains(key_id),
+            None => true,
+        };
+    }
+
+    /// Returns the first walkable direction, or `IVec2::ZERO` if there is none.
+    pub fn first_walkable(&self, dirs: &[IVec2]) -> IVec2 {
+        for &dir in dirs {
+            if self.is_walkable(dir) {
+                return dir;
+            }
+        }
+        return IVec2::ZERO;
+    }
+}
+
+// Rules
+
+// go forward, else turn toward `clockwise`, else reverse
+fn steer_forward(ctx: &SteeringContext) -> IVec2 {
+    return ctx.first_walkable(&[ctx.dir, rotate_dir(ctx.dir, ctx.clockwise), -ctx.dir]);
+}
+
+fn steer_wall_follow_left(ctx: &SteeringContext) -> IVec2 {
+    return ctx.first_walkable(&[
+        rotate_dir(ctx.dir, false),
+        ctx.dir,
+        rotate_dir(ctx.dir, true),
+        -ctx.dir,
+    ]);
+}
+
+fn steer_wall_follow_right(ctx: &SteeringContext) -> IVec2 {
+    return ctx.first_walkable(&[
+        rotate_dir(ctx.dir, true),
+        ctx.dir,
+        rotate_dir(ctx.dir, false),
+        -ctx.dir,
+    ]);
+}
+
+fn steer_bouncer(ctx: &SteeringContext) -> IVec2 {
+    return ctx.first_walkable(&[ctx.dir, -ctx.dir]);
+}
+
+// picks any open direction except back, so the same seed always walks the same route
+fn steer_random(ctx: &SteeringContext) -> IVec2 {
+    let options: Vec<IVec2> = [
+        ctx.dir,
+        rotate_dir(ctx.dir, true),
+        rotate_dir(ctx.dir, false),
+    ]
+    .into_iter()
+    .filter(|dir| ctx.is_walkable(*dir))
+    .collect();
+
+    if options.is_empty() {
+        return ctx.first_walkable(&[-ctx.dir]);
+    }
+
+    let mut rng = StdRng::seed_from_u64(ctx.seed.wrapping_add(ctx.steps as u64));
+    return options[rng.gen_range(0..options.len())];
+}
+
+// heads toward the closest adventurer, never reversing unless it has to
+fn steer_chase(ctx: &SteeringContext) -> IVec2 {
+    let mut best_dir = IVec2::ZERO;
+    let mut best_dist = i32::MAX;
+
+    for dir in [
+        ctx.dir,
+        rotate_dir(ctx.dir, ctx.clockwise),
+        rotate_dir(ctx.dir, !ctx.clockwise),
+    ] {
+        if !ctx.is_walkable(dir) {
+            continue;
+        }
+        let next = ctx.coord + dir;
+        let dist = ctx
+            .adventurer_coords
+            .iter()
+            .map(|coord| {
+                let delta = (*coord - next).abs();
+                delta.x + delta.y
+            })
+            .min()
+            .unwrap_or(0);
+        if dist < best_dist {
+            best_dist = dist;
+            best_dir = dir;
+        }
+    }
+
+    if best_dir == IVec2::ZERO {
+        return ctx.first_walkable(&[-ctx.dir]);
+    }
+    return best_dir;
+}
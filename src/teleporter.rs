@@ -10,9 +10,11 @@ impl Plugin for TeleporterPlugin {
             Update,
             (
                 initialize_enter_portals,
-                initialize_teleporters,
+                initialize_teleporters.after(initialize_enter_portals),
                 hide_inactive_exit_portals,
                 toggle_exit_portals,
+                preview_teleporters.run_if(in_state(MapLoadState::Done)),
+                tick_portal_cooldowns.run_if(in_state(PauseState::Running)),
             ),
         );
     }
@@ -34,20 +36,44 @@ pub struct EnterPortal {
     pub exit_portal: Option<Entity>,
 }
 
+/// An object can be both an `EnterPortal` and an `ExitPortal`, two of those
+/// pointing at each other make a two way pair.
 #[derive(Debug, Component)]
 pub struct ExitPortal {
     id: u16,
     pub exit_dir: IVec2,
-    active: bool,
+    pub active: bool,
+    // seconds before a mover coming out of here can use any portal again
+    pub cooldown: f32,
 }
 
 #[derive(Debug, Component)]
 pub struct Teleporter {
-    enter_portal_id: i64,
-    enter_portal: Option<Entity>,
+    enter_portal_ids: Vec<i64>,
+    enter_portals: Vec<Entity>,
     exit_portal_ids: Vec<i64>,
     exit_portals: Vec<Entity>,
-    active_exit_portal: usize,
+    pub active_exit_portal: usize,
+}
+
+/// Keeps a mover that just came out of a portal from going right back in.
+#[derive(Debug, Clone, Component)]
+pub struct PortalCooldown {
+    pub arrived_through: Option<Entity>,
+    remaining: f32,
+}
+
+impl PortalCooldown {
+    pub fn new(arrived_through: Option<Entity>, cooldown: f32) -> Self {
+        return PortalCooldown {
+            arrived_through,
+            remaining: cooldown,
+        };
+    }
+
+    pub fn blocks(&self, portal: Entity) -> bool {
+        return self.remaining > 0.0 || self.arrived_through == Some(portal);
+    }
 }
 
 // Hydrators
@@ -72,20 +98,40 @@ pub fn hydrate_enter_portal(entity_commands: &mut EntityCommands, object_data: &
 pub fn hydrate_exit_portal(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
     let x = get_property_value_from_object_or_default_i(object_data, "exit_dir_x", 0);
     let y = get_property_value_from_object_or_default_i(object_data, "exit_dir_y", 0);
+    let cooldown = get_property_value_from_object_or_default_f(object_data, "cooldown", 0.0);
 
+    // exit portals without a teleporter are always open, teleporters pick
+    // theirs when they're initialized
     entity_commands.insert(ExitPortal {
         id: object_data.id,
         exit_dir: IVec2::new(x as i32, y as i32),
-        active: false,
+        active: true,
+        cooldown: cooldown as f32,
     });
 }
 
 pub fn hydrate_teleporter(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let mut enter_portal_ids = Vec::<i64>::new();
     let enter_portal_id =
         get_property_value_from_object_or_default_i(object_data, "enter_portal", 0);
+    if enter_portal_id != 0 {
+        enter_portal_ids.push(enter_portal_id);
+    }
+
+    let mut idx = 1;
+    loop {
+        let enter_portal_name = format!("{}{}", "enter_portal_", idx);
+        let enter_portal_id =
+            get_property_value_from_object_or_default_i(object_data, &enter_portal_name, -1);
+        if enter_portal_id == -1 {
+            break;
+        }
+        enter_portal_ids.push(enter_portal_id);
+        idx += 1;
+    }
 
-    if enter_portal_id == 0 {
-        println!("Hydrated ExitPortal with no exit_portal assigned!");
+    if enter_portal_ids.is_empty() {
+        println!("Hydrated Teleporter with no enter_portal assigned!");
     }
 
     let mut exit_portal_ids = Vec::<i64>::new();
@@ -104,8 +150,8 @@ pub fn hydrate_teleporter(entity_commands: &mut EntityCommands, object_data: &Ob
 
     entity_commands.insert((
         Teleporter {
-            enter_portal_id,
-            enter_portal: None,
+            enter_portal_ids,
+            enter_portals: Vec::new(),
             exit_portal_ids,
             exit_portals: Vec::new(),
             active_exit_portal: 0,
@@ -134,15 +180,19 @@ fn initialize_enter_portals(
 
 fn initialize_teleporters(
     mut teleporter_q: Query<(Entity, &mut Teleporter), With<Uninintialized>>,
-    enter_q: Query<(Entity, &mut EnterPortal)>,
+    mut enter_q: Query<(Entity, &mut EnterPortal)>,
     mut exit_q: Query<(Entity, &mut ExitPortal)>,
     mut commands: Commands,
 ) {
     for (teleporter_entity, mut teleporter) in teleporter_q.iter_mut() {
-        for (enter_entity, enter_portal) in enter_q.iter() {
-            if enter_portal.id == (teleporter.enter_portal_id as u16) {
-                teleporter.enter_portal = Some(enter_entity);
-                break;
+        let enter_ids = teleporter.enter_portal_ids.clone();
+
+        for id in enter_ids.iter() {
+            for (enter_entity, enter_portal) in enter_q.iter() {
+                if enter_portal.id == (*id as u16) {
+                    teleporter.enter_portals.push(enter_entity);
+                    break;
+                }
             }
         }
 
@@ -159,6 +209,15 @@ fn initialize_teleporters(
             }
         }
 
+        // every enter portal starts out pointing at the active exit
+        if let Some(&exit_entity) = teleporter.exit_portals.first() {
+            for &enter_entity in &teleporter.enter_portals {
+                if let Ok((_, mut enter_portal)) = enter_q.get_mut(enter_entity) {
+                    enter_portal.exit_portal = Some(exit_entity);
+                }
+            }
+        }
+
         commands
             .entity(teleporter_entity)
             .remove::<Uninintialized>();
@@ -176,14 +235,14 @@ fn hide_inactive_exit_portals(mut exit_q: Query<(&mut Sprite, &ExitPortal)>) {
 }
 
 fn toggle_exit_portals(
-    mut ev_mouse_click: EventReader<MouseClickEvent>,
+    mut ev_activate: EventReader<ActivateEvent>,
     mut teleporter_q: Query<&mut Teleporter, With<ClickableArea>>,
     mut exit_q: Query<(Entity, &mut ExitPortal)>,
     mut enter_q: Query<&mut EnterPortal>,
     audio_server: Res<AudioServer>,
     mut commands: Commands,
 ) {
-    for e in ev_mouse_click.read() {
+    for e in ev_activate.read() {
         let entity = e.0;
         if let Ok(mut teleporter) = teleporter_q.get_mut(entity) {
             if let Ok((_, mut exit)) =
@@ -200,7 +259,7 @@ fn toggle_exit_portals(
             {
                 exit_portal.active = true;
 
-                if let Some(enter_portal) = teleporter.enter_portal {
+                for &enter_portal in &teleporter.enter_portals {
                     if let Ok(mut enter) = enter_q.get_mut(enter_portal) {
                         enter.exit_portal = Some(exit_entity);
                     }
@@ -211,3 +270,50 @@ fn toggle_exit_portals(
         }
     }
 }
+
+// outlines the exit portal the hovered teleporter would switch to
+fn preview_teleporters(
+    hovered: Res<HoveredArea>,
+    focus: Res<ClickableFocus>,
+    teleporter_q: Query<&Teleporter>,
+    exit_q: Query<&Transform, With<ExitPortal>>,
+    mut gizmos: Gizmos,
+) {
+    let previewed = hovered.previewed(&focus);
+    if let Some(teleporter) = previewed.and_then(|entity| teleporter_q.get(entity).ok()) {
+        if teleporter.exit_portals.is_empty() {
+            return;
+        }
+
+        let current = teleporter.exit_portals[teleporter.active_exit_portal];
+        let next = teleporter.exit_portals
+            [(teleporter.active_exit_portal + 1) % teleporter.exit_portals.len()];
+        if current == next {
+            return;
+        }
+
+        for (exit_entity, color) in [(current, PREVIEW_REMOVE_COLOR), (next, PREVIEW_ADD_COLOR)] {
+            if let Ok(transform) = exit_q.get(exit_entity) {
+                draw_outline(
+                    &mut gizmos,
+                    transform.translation.truncate(),
+                    &CollisionShape::FullTile,
+                    color,
+                );
+            }
+        }
+    }
+}
+
+fn tick_portal_cooldowns(
+    mut cooldown_q: Query<(Entity, &mut PortalCooldown)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut cooldown) in cooldown_q.iter_mut() {
+        cooldown.remaining -= time.delta_seconds();
+        if cooldown.remaining <= 0.0 && cooldown.arrived_through.is_none() {
+            commands.entity(entity).remove::<PortalCooldown>();
+        }
+    }
+}
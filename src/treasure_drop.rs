@@ -0,0 +1,223 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::*;
+
+// Constants
+
+// how far (in tiles) to look for a safe spot before giving up and dropping in place
+const MAX_DROP_SEARCH: i32 = 5;
+
+// Plugin
+
+pub struct TreasureDropPlugin;
+impl Plugin for TreasureDropPlugin {
+    fn build(&self, app: &mut App) {
+        // reacts to the drops of the previous tick, so its kills still go
+        // through `filter_mover_kills` like any other
+        app.add_event::<TreasureDropped>().add_systems(
+            FixedUpdate,
+            fail_level_on_lost_treasure.before(filter_mover_kills),
+        );
+    }
+}
+
+// Events
+
+/// Raised for every treasure a dying mover lets go of.
+#[derive(Debug, Clone, Event)]
+pub struct TreasureDropped {
+    pub treasure: Entity,
+    // fell into a pit and can't be picked up again
+    pub lost: bool,
+}
+
+// Data
+
+/// Where a treasure ends up when whoever carries it dies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreasureDrop {
+    /// Stays where it was floating
+    InPlace,
+    /// Snaps to the closest walkable tile that isn't an open pit
+    Nearest,
+    /// Spreads out over the tiles around the carrier
+    Scatter,
+    /// Stays where it was floating, but is lost if that's over an open pit
+    Pit,
+    /// Goes back to where it was placed in the map
+    Spawn,
+}
+
+impl TreasureDrop {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "in_place" => Some(TreasureDrop::InPlace),
+            "nearest" => Some(TreasureDrop::Nearest),
+            "scatter" => Some(TreasureDrop::Scatter),
+            "pit" => Some(TreasureDrop::Pit),
+            "spawn" => Some(TreasureDrop::Spawn),
+            _ => None,
+        };
+    }
+}
+
+type DroppedTreasure = (
+    &'static mut Transform,
+    &'static mut Collider,
+    &'static mut Visibility,
+    &'static mut Sprite,
+    &'static Treasure,
+);
+type PlanksOverPits = (Without<Pit>, Without<Treasure>);
+
+#[derive(SystemParam)]
+pub struct TreasureDropper<'w, 's> {
+    treasure_q: Query<'w, 's, DroppedTreasure, Without<Pit>>,
+    pit_q: Query<'w, 's, &'static Transform, With<Pit>>,
+    planks_q: Query<'w, 's, (&'static Transform, &'static Planks), PlanksOverPits>,
+    map_server: Res<'w, MapServer>,
+    ev_treasure_dropped: EventWriter<'w, TreasureDropped>,
+}
+
+impl<'w, 's> TreasureDropper<'w, 's> {
+    /// Lets go of `treasure`, `scatter_idx` spreads out treasure dropped together.
+    pub fn drop(&mut self, treasure_entity: Entity, scatter_idx: usize) {
+        let open_pits = self.open_pit_coords();
+        let map = self.map_server.get_current_map();
+
+        if let Ok((mut transform, mut collider, mut visibility, mut sprite, treasure)) =
+            self.treasure_q.get_mut(treasure_entity)
+        {
+            let here = translation_to_coord(transform.translation);
+            let is_safe = |coord: IVec2| {
+                return floor_tile_from_coord(coord, map) != FloorTile::Wall
+                    && !open_pits.contains(&coord);
+            };
+
+            let coord = match treasure.drop {
+                TreasureDrop::InPlace | TreasureDrop::Pit => here,
+                TreasureDrop::Nearest => nearest_coord(here, &is_safe).unwrap_or(here),
+                TreasureDrop::Scatter => scatter_coord(here, scatter_idx, &is_safe)
+                    .or_else(|| nearest_coord(here, &is_safe))
+                    .unwrap_or(here),
+                TreasureDrop::Spawn => treasure.spawn,
+            };
+            let lost = treasure.drop == TreasureDrop::Pit && open_pits.contains(&coord);
+            // it may have been halfway through a portal
+            sprite.color.set_a(1.0);
+
+            if treasure.drop != TreasureDrop::InPlace && treasure.drop != TreasureDrop::Pit {
+                transform.translation.x = coord_to_pos(coord.x as f32);
+                transform.translation.y = coord_to_pos(coord.y as f32);
+            }
+            if lost {
+                *visibility = Visibility::Hidden;
+            } else {
+                collider.active = true;
+            }
+
+            self.ev_treasure_dropped.send(TreasureDropped {
+                treasure: treasure_entity,
+                lost,
+            });
+        }
+    }
+
+    fn open_pit_coords(&self) -> Vec<IVec2> {
+        let planked: Vec<IVec2> = self
+            .planks_q
+            .iter()
+            .filter(|(_, planks)| planks.active)
+            .map(|(transform, _)| translation_to_coord(transform.translation))
+            .collect();
+
+        return self
+            .pit_q
+            .iter()
+            .map(|transform| translation_to_coord(transform.translation))
+            .filter(|coord| !planked.contains(coord))
+            .collect();
+    }
+}
+
+// Systems
+
+// a required treasure at the bottom of a pit means the level can't be finished
+fn fail_level_on_lost_treasure(
+    mut ev_treasure_dropped: EventReader<TreasureDropped>,
+    mut ev_mover_killed: EventWriter<MoverKilled>,
+    treasure_q: Query<&Treasure>,
+    adventurer_q: Query<Entity, (With<Adventurer>, With<Mover>)>,
+) {
+    for dropped in ev_treasure_dropped.read() {
+        if !dropped.lost {
+            continue;
+        }
+        if let Ok(treasure) = treasure_q.get(dropped.treasure) {
+            if !treasure.required {
+                continue;
+            }
+            for adventurer_entity in adventurer_q.iter() {
+                ev_mover_killed.send(MoverKilled {
+                    victim: adventurer_entity,
+                    cause: KillCause::TreasureLost,
+                    killer: None,
+                });
+            }
+        }
+    }
+}
+
+// Helpers
+
+fn translation_to_coord(translation: Vec3) -> IVec2 {
+    return IVec2::new(
+        pos_to_coord(translation.x).round() as i32,
+        pos_to_coord(translation.y).round() as i32,
+    );
+}
+
+fn nearest_coord(from: IVec2, is_safe: &dyn Fn(IVec2) -> bool) -> Option<IVec2> {
+    for radius in 0..=MAX_DROP_SEARCH {
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                // only the ring at this radius, inner tiles were already checked
+                if x.abs() != radius && y.abs() != radius {
+                    continue;
+                }
+                let coord = from + IVec2::new(x, y);
+                if is_safe(coord) {
+                    return Some(coord);
+                }
+            }
+        }
+    }
+    return None;
+}
+
+fn scatter_coord(
+    from: IVec2,
+    scatter_idx: usize,
+    is_safe: &dyn Fn(IVec2) -> bool,
+) -> Option<IVec2> {
+    let spots: Vec<IVec2> = [
+        IVec2::ZERO,
+        IVec2::X,
+        IVec2::NEG_X,
+        IVec2::Y,
+        IVec2::NEG_Y,
+        IVec2::ONE,
+        IVec2::NEG_ONE,
+        IVec2::new(1, -1),
+        IVec2::new(-1, 1),
+    ]
+    .into_iter()
+    .map(|offset| from + offset)
+    .filter(|coord| is_safe(*coord))
+    .collect();
+
+    if spots.is_empty() {
+        return None;
+    }
+    return Some(spots[scatter_idx % spots.len()]);
+}
@@ -1,13 +1,25 @@
-use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
 use rand::Rng;
 
 use crate::{
-    coord_to_pos, Adventurer, Mover, SceneState, TreasuresLabel, SUCCESS_COLOR, TEXT_COLOR,
+    filter_mover_kills, get_property_value_from_object_or_default_b,
+    get_property_value_from_object_or_default_i, get_property_value_from_object_or_default_s,
+    Adventurer, ComponentHydrators, Mover, MoverKilled, ObjectData, SceneState, SpriteAnimation,
+    TreasureDrop, TreasureDropper, TreasuresLabel, SCALE, SUCCESS_COLOR, TEXT_COLOR, TILE_WIDTH,
 };
 
 // Constants
 
-const TREASURE_SPEED: f32 = 2.0;
+// distance between treasures along the mover's path
+const TREASURE_SPACING: f32 = TILE_WIDTH * SCALE;
+// how far from a portal treasure starts fading out (or is fully faded back in)
+const TREASURE_FADE_DISTANCE: f32 = TILE_WIDTH * SCALE * 0.5;
+// the path is only recorded every so often
+const PATH_SAMPLE_DISTANCE: f32 = TILE_WIDTH * SCALE * 0.25;
+// movers never cover this much in a frame unless they teleported
+const TELEPORT_DISTANCE: f32 = TILE_WIDTH * SCALE * 1.5;
 const TREASURE_MIN_ROT: f32 = 0.5;
 const TREASURE_MAX_ROT: f32 = 2.0;
 
@@ -15,11 +27,18 @@ const TREASURE_MAX_ROT: f32 = 2.0;
 pub struct TreasureTrainPlugin;
 impl Plugin for TreasureTrainPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_treasure_trains, update_treasure_count))
+        app.add_systems(Startup, add_hydrators)
+            .add_systems(Update, (update_treasure_trains, update_treasure_count))
+            .add_systems(
+                FixedUpdate,
+                transfer_treasure_on_kill.after(filter_mover_kills),
+            )
             .add_systems(OnEnter(SceneState::Stable), count_total_treasures)
             .insert_resource(TreasureCount {
                 player_treasures: 0,
                 map_treasures: 0,
+                player_bonus_treasures: 0,
+                map_bonus_treasures: 0,
             });
     }
 }
@@ -27,9 +46,13 @@ impl Plugin for TreasureTrainPlugin {
 // Resources
 
 #[derive(Debug, Resource)]
+/// `player_treasures` and `map_treasures` only count required treasure, bonus
+/// treasure never blocks the exit.
 pub struct TreasureCount {
     pub player_treasures: u16,
     pub map_treasures: u16,
+    pub player_bonus_treasures: u16,
+    pub map_bonus_treasures: u16,
 }
 
 // Components
@@ -38,68 +61,308 @@ pub struct TreasureCount {
 pub struct Treasure {
     pub following: Option<Entity>,
     pub rot_speed: f32,
+    pub kind: TreasureKind,
+    // points it's worth once carried by the adventurer
+    pub value: u32,
+    // bonus treasure isn't needed to leave through the exit
+    pub required: bool,
+    pub spawn: IVec2,
+    // what happens when whoever carries it dies
+    pub drop: TreasureDrop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreasureKind {
+    Coin,
+    Gem,
+    Idol,
+}
+
+impl TreasureKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "coin" => Some(TreasureKind::Coin),
+            "gem" => Some(TreasureKind::Gem),
+            "idol" => Some(TreasureKind::Idol),
+            _ => None,
+        };
+    }
+
+    fn default_value(&self) -> u32 {
+        return match self {
+            TreasureKind::Coin => 10,
+            TreasureKind::Gem => 50,
+            TreasureKind::Idol => 100,
+        };
+    }
+
+    fn animation_name(&self) -> &'static str {
+        return match self {
+            TreasureKind::Coin => "treasure_coin",
+            TreasureKind::Gem => "treasure_gem",
+            TreasureKind::Idol => "treasure_idol",
+        };
+    }
 }
-impl Default for Treasure {
-    fn default() -> Self {
-        Self {
-            following: None,
-            rot_speed: rand::thread_rng().gen_range(TREASURE_MIN_ROT..TREASURE_MAX_ROT),
+
+// Hydrators
+
+fn hydrate_treasure(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+    let kind_name = get_property_value_from_object_or_default_s(object_data, "kind", "coin".into());
+    let kind = TreasureKind::from_name(&kind_name).unwrap_or_else(|| {
+        println!("unknown treasure kind:{}, using coin", kind_name);
+        TreasureKind::Coin
+    });
+    let value = get_property_value_from_object_or_default_i(
+        object_data,
+        "value",
+        kind.default_value() as i64,
+    );
+    let required = get_property_value_from_object_or_default_b(object_data, "required", true);
+    let drop_name =
+        get_property_value_from_object_or_default_s(object_data, "drop", "in_place".into());
+    let drop = TreasureDrop::from_name(&drop_name).unwrap_or_else(|| {
+        println!("unknown treasure drop:{}, using in_place", drop_name);
+        TreasureDrop::InPlace
+    });
+
+    match object_data
+        .sprite_sheet
+        .animations
+        .get(kind.animation_name())
+    {
+        Some(frames) => {
+            let mut animation = SpriteAnimation::default();
+            animation.play(frames, true);
+            entity_commands.insert((
+                animation,
+                TextureAtlas {
+                    layout: object_data.sprite_sheet.texture_atlas_layout.clone(),
+                    index: frames[0].tile_id as usize,
+                },
+            ));
         }
+        None => println!("no tile animation named:{}", kind.animation_name()),
     }
+
+    entity_commands.insert(Treasure {
+        following: None,
+        rot_speed: rand::thread_rng().gen_range(TREASURE_MIN_ROT..TREASURE_MAX_ROT),
+        kind,
+        value: value.max(0) as u32,
+        required,
+        spawn: IVec2::new(object_data.x as i32, object_data.y as i32),
+        drop,
+    });
 }
 
-#[derive(Debug, Component)]
+#[derive(Debug, Clone, Copy)]
+pub struct PathPoint {
+    pub pos: Vec2,
+    // the mover teleported here, so it never walked to the next (older) point
+    pub teleported: bool,
+}
+
+/// Treasure following a mover, spaced out along the path it walked.
+#[derive(Debug, Clone, Component)]
 pub struct TreasureTrain {
     pub mover: Entity,
     pub treasures: Vec<Entity>,
-    pub head_spot: IVec2,
-    pub target_spots: Vec<IVec2>,
+    // newest first
+    pub path: VecDeque<PathPoint>,
+    // the mover went through a portal and is still on its way out of this one
+    pub teleport_exit: Option<Vec2>,
+}
+
+impl TreasureTrain {
+    pub fn new(mover: Entity, treasure: Entity, mover_pos: Vec2) -> Self {
+        return TreasureTrain {
+            mover,
+            treasures: vec![treasure],
+            path: VecDeque::from([PathPoint {
+                pos: mover_pos,
+                teleported: false,
+            }]),
+            teleport_exit: None,
+        };
+    }
+
+    /// The mover entered a portal, the train waits there until it comes out
+    /// of `exit_pos`.
+    pub fn teleport(&mut self, exit_pos: Vec2) {
+        self.teleport_exit = Some(exit_pos);
+    }
+
+    // returns where the head of the train is
+    fn record(&mut self, mover_pos: Vec2) -> Vec2 {
+        let last = self.path.front().map(|p| p.pos).unwrap_or(mover_pos);
+        let distance = last.distance(mover_pos);
+
+        if let Some(exit_pos) = self.teleport_exit {
+            if mover_pos.distance(exit_pos) > PATH_SAMPLE_DISTANCE {
+                return last;
+            }
+            self.teleport_exit = None;
+            self.path.push_front(PathPoint {
+                pos: mover_pos,
+                teleported: true,
+            });
+        } else if self.path.is_empty() || distance >= PATH_SAMPLE_DISTANCE {
+            self.path.push_front(PathPoint {
+                pos: mover_pos,
+                teleported: distance >= TELEPORT_DISTANCE,
+            });
+        }
+
+        // forget whatever no treasure can reach anymore
+        let needed = self.treasures.len() as f32 * TREASURE_SPACING + TREASURE_FADE_DISTANCE;
+        let mut walked = 0.0;
+        for i in 1..self.path.len() {
+            if !self.path[i - 1].teleported {
+                walked += self.path[i - 1].pos.distance(self.path[i].pos);
+            }
+            if walked > needed {
+                self.path.truncate(i + 1);
+                break;
+            }
+        }
+
+        return mover_pos;
+    }
+
+    /// Position `distance` behind `head` along the path, and how visible
+    /// something there is given the portals around it.
+    fn sample(&self, head: Vec2, distance: f32) -> (Vec2, f32) {
+        let mut pos: Option<Vec2> = None;
+        // distances along the path where the mover teleported
+        let mut portals = Vec::<f32>::new();
+
+        let mut walked = 0.0;
+        let mut prev = PathPoint {
+            pos: head,
+            teleported: false,
+        };
+        for &point in &self.path {
+            if prev.teleported {
+                portals.push(walked);
+            } else {
+                let len = prev.pos.distance(point.pos);
+                if pos.is_none() && walked + len >= distance {
+                    let t = if len > 0.0 {
+                        (distance - walked) / len
+                    } else {
+                        0.0
+                    };
+                    pos = Some(prev.pos.lerp(point.pos, t));
+                }
+                walked += len;
+            }
+            if walked > distance + TREASURE_FADE_DISTANCE {
+                break;
+            }
+            prev = point;
+        }
+
+        // fade out walking into a portal, back in walking out of the other
+        let alpha = portals
+            .iter()
+            .map(|portal| ((distance - portal).abs() / TREASURE_FADE_DISTANCE).min(1.0))
+            .fold(1.0, f32::min);
+
+        return (pos.unwrap_or(prev.pos), alpha);
+    }
 }
 
 // Systems
 
+fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
+    hydrators.register_hydrator("Treasure", hydrate_treasure);
+}
+
 fn update_treasure_trains(
     mut treasure_train_q: Query<&mut TreasureTrain>,
-    mut treasure_q: Query<(&mut Transform, &Treasure)>,
-    mover_q: Query<&Mover>,
+    mut treasure_q: Query<(&mut Transform, &mut Sprite, &Treasure), Without<Mover>>,
+    mover_q: Query<&Transform, With<Mover>>,
     time: Res<Time>,
 ) {
     for mut treasure_train in treasure_train_q.iter_mut() {
-        // fill target spots
-        while treasure_train.treasures.len() > treasure_train.target_spots.len() {
-            let last_coord = treasure_train.target_spots.last().unwrap().clone();
-            treasure_train.target_spots.push(last_coord);
+        // a dead mover's train stays where it is until it's handed over
+        let head = match mover_q.get(treasure_train.mover) {
+            Ok(mover_transform) => mover_transform.translation.truncate(),
+            Err(_) => match treasure_train.path.front() {
+                Some(point) => point.pos,
+                None => continue,
+            },
+        };
+        let head = treasure_train.record(head);
+
+        // move, fade & spin treasures
+        for (i, &treasure_entity) in treasure_train.treasures.iter().enumerate() {
+            if let Ok((mut treasure_transform, mut sprite, treasure)) =
+                treasure_q.get_mut(treasure_entity)
+            {
+                let (pos, alpha) = treasure_train.sample(head, (i + 1) as f32 * TREASURE_SPACING);
+                treasure_transform.translation.x = pos.x;
+                treasure_transform.translation.y = pos.y;
+                if sprite.color.a() != alpha {
+                    sprite.color.set_a(alpha);
+                }
+                treasure_transform.rotate_z(treasure.rot_speed * time.delta_seconds());
+            }
         }
+    }
+}
 
-        // update target spots
-        if let Ok(mover) = mover_q.get(treasure_train.mover) {
-            if mover.target != treasure_train.head_spot {
-                for i in (0..treasure_train.target_spots.len()).rev() {
-                    if i == 0 {
-                        treasure_train.target_spots[i] = treasure_train.head_spot;
-                    } else {
-                        treasure_train.target_spots[i] = treasure_train.target_spots[i - 1];
-                    }
+// the killer takes the victim's treasure, otherwise it's dropped
+pub fn transfer_treasure_on_kill(
+    mut ev_mover_killed: EventReader<MoverKilled>,
+    mut treasure_train_q: Query<(Entity, &mut TreasureTrain)>,
+    mut treasure_dropper: TreasureDropper,
+    mut commands: Commands,
+) {
+    for kill in ev_mover_killed.read() {
+        let mut did_treasure_transfer = false;
+
+        // When killed by a mover
+        if let Some(killer) = kill.killer {
+            let mut killed_train_e: Option<Entity> = None;
+            let mut killed_train: Option<Mut<'_, TreasureTrain>> = None;
+            let mut killer_train: Option<Mut<'_, TreasureTrain>> = None;
+
+            for (entity, treasure_train) in treasure_train_q.iter_mut() {
+                if treasure_train.mover == kill.victim {
+                    killed_train = Some(treasure_train);
+                    killed_train_e = Some(entity);
+                } else if treasure_train.mover == killer {
+                    killer_train = Some(treasure_train);
                 }
-                treasure_train.head_spot = mover.target;
+            }
+
+            if let (Some(killed_train_e), Some(killed_train), Some(mut killer_train)) =
+                (killed_train_e, killed_train, killer_train)
+            {
+                for treasure in &killed_train.treasures {
+                    killer_train.treasures.push(*treasure);
+                }
+
+                // despawn the treasure train
+                commands.entity(killed_train_e).despawn();
+
+                did_treasure_transfer = true;
             }
         }
 
-        // move & spin treasures
-        let mut i = 0;
-        for &treasure_entity in &treasure_train.treasures {
-            if let Ok((mut treasure_transform, treasure)) = treasure_q.get_mut(treasure_entity) {
-                let coord = treasure_train.target_spots[i];
-                let mvmt = Vec3::new(
-                    coord_to_pos(coord.x as f32),
-                    coord_to_pos(coord.y as f32),
-                    treasure_transform.translation.z,
-                ) - treasure_transform.translation;
-                treasure_transform.translation =
-                    treasure_transform.translation + mvmt * TREASURE_SPEED * time.delta_seconds();
-                treasure_transform.rotate_z(treasure.rot_speed * time.delta_seconds());
+        if !did_treasure_transfer {
+            for (entity, treasure_train) in treasure_train_q.iter() {
+                if treasure_train.mover == kill.victim {
+                    for (idx, &treasure_entity) in treasure_train.treasures.iter().enumerate() {
+                        treasure_dropper.drop(treasure_entity, idx);
+                    }
+                    // despawn the treasure train
+                    commands.entity(entity).despawn();
+                    break;
+                }
             }
-            i += 1;
         }
     }
 }
@@ -107,20 +370,39 @@ fn update_treasure_trains(
 fn update_treasure_count(
     mut treasure_count: ResMut<TreasureCount>,
     treasure_train_q: Query<&TreasureTrain>,
+    treasure_q: Query<&Treasure>,
     adventurer_q: Query<Entity, With<Adventurer>>,
     mut treasure_label_q: Query<&mut Text, With<TreasuresLabel>>,
 ) {
     if let Ok(mut treasure_label) = treasure_label_q.get_single_mut() {
         let mut treasures_picked_up: u16 = 0;
+        let mut bonus_treasures_picked_up: u16 = 0;
 
         for treasure_train in treasure_train_q.iter() {
             if let Ok(_) = adventurer_q.get(treasure_train.mover) {
-                treasures_picked_up = treasure_train.treasures.len() as u16;
+                for treasure in treasure_q.iter_many(&treasure_train.treasures) {
+                    if treasure.required {
+                        treasures_picked_up += 1;
+                    } else {
+                        bonus_treasures_picked_up += 1;
+                    }
+                }
             }
         }
 
         treasure_count.player_treasures = treasures_picked_up;
-        treasure_label.sections[1].value = treasure_count.player_treasures.to_string();
+        treasure_count.player_bonus_treasures = bonus_treasures_picked_up;
+
+        let mut label = format!(
+            "{}/{}",
+            treasure_count.player_treasures, treasure_count.map_treasures
+        );
+        if treasure_count.map_bonus_treasures > 0 {
+            label += &format!(" + {} bonus", treasure_count.player_bonus_treasures);
+        }
+        if treasure_label.sections[1].value != label {
+            treasure_label.sections[1].value = label;
+        }
 
         let text_color = if treasure_count.player_treasures == treasure_count.map_treasures {
             Color::hex(SUCCESS_COLOR).expect("invalid color hex")
@@ -134,6 +416,8 @@ fn update_treasure_count(
 }
 
 fn count_total_treasures(treasure_q: Query<&Treasure>, mut treasure_count: ResMut<TreasureCount>) {
-    treasure_count.map_treasures = treasure_q.iter().count() as u16;
+    treasure_count.map_treasures = treasure_q.iter().filter(|t| t.required).count() as u16;
+    treasure_count.map_bonus_treasures = treasure_q.iter().filter(|t| !t.required).count() as u16;
     treasure_count.player_treasures = 0;
+    treasure_count.player_bonus_treasures = 0;
 }
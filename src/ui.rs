@@ -5,8 +5,9 @@ use bevy::{
 
 use crate::{
     get_property_value_from_object_or_default_f, get_property_value_from_object_or_default_s,
-    AudioServer, BackgroundLoop, ComponentHydrators, MapLoadState, MapServer, ObjectData,
-    SceneState, Uninintialized, HIGH_RES_LAYERS, PIXEL_PERFECT_LAYERS, RES_HEIGHT, RES_WIDTH,
+    AudioServer, BackgroundLoop, ClickableArea, ClickableFocus, CollisionShape, ComponentHydrators,
+    HoveredArea, MapLoadState, ObjectData, Uninintialized, HIGH_RES_LAYERS, PIXEL_PERFECT_LAYERS,
+    RES_HEIGHT, RES_WIDTH,
 };
 
 // Constants
@@ -17,6 +18,11 @@ pub const SUCCESS_COLOR: &str = "6EAA78";
 const NORMAL_BUTTON: Color = Color::rgba(0.0, 0.0, 0.0, 0.3);
 const HOVERED_BUTTON: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
 const PRESSED_BUTTON: Color = Color::rgba(0.0, 0.0, 0.0, 1.0);
+// button sizes are in canvas pixels, the text is drawn at 4x and scaled down
+const BUTTON_CHAR_WIDTH: f32 = 8.0;
+const BUTTON_HEIGHT: f32 = 15.0;
+// above anything in the level, so a button always wins the click
+const BUTTON_Z: f32 = 100.0;
 
 // Plugin
 
@@ -27,7 +33,7 @@ impl Plugin for UIPlugin {
             .add_systems(Update, (initialize_background_loop, initialize_labels))
             .add_systems(
                 Update,
-                start_button_system.run_if(in_state(MapLoadState::Done)),
+                update_button_colors.run_if(in_state(MapLoadState::Done)),
             );
     }
 }
@@ -48,8 +54,8 @@ pub struct LabelProperties {
     pub offset: Vec2,
 }
 
-#[derive(Debug, Component, Default)]
-pub struct StartButton;
+#[derive(Debug, Component)]
+pub struct MenuButton;
 
 // Hydrators
 
@@ -60,18 +66,11 @@ pub fn hydrate_label(entity_commands: &mut EntityCommands, object_data: &ObjectD
         ..Default::default()
     };
 
-    let anchor_s =
-        get_property_value_from_object_or_default_s(object_data, "anchor", "TopLeft".into());
-
-    let anchor = if anchor_s == "TopLeft" {
-        Anchor::TopLeft
-    } else {
-        if anchor_s == "TopRight" {
-            Anchor::TopRight
-        } else {
-            Anchor::Center
-        }
-    };
+    let anchor = anchor_from_name(&get_property_value_from_object_or_default_s(
+        object_data,
+        "anchor",
+        "TopLeft".into(),
+    ));
 
     let mut sections = Vec::<TextSection>::new();
     let mut idx = 0;
@@ -111,7 +110,13 @@ pub fn hydrate_label(entity_commands: &mut EntityCommands, object_data: &ObjectD
     ));
 }
 
-fn hydrate_start_button(entity_commands: &mut EntityCommands, object_data: &ObjectData) {
+/// Spawns a menu button with `button` on it, so it can be told apart from other buttons.
+/// It's a `ClickableArea` like any other, so it can be clicked, tapped or focused.
+pub fn hydrate_button(
+    entity_commands: &mut EntityCommands,
+    object_data: &ObjectData,
+    button: impl Bundle,
+) {
     let text_style = TextStyle {
         font_size: 30.0,
         color: Color::hex(TEXT_COLOR).expect("invalid hex color"),
@@ -120,44 +125,61 @@ fn hydrate_start_button(entity_commands: &mut EntityCommands, object_data: &Obje
 
     let text =
         get_property_value_from_object_or_default_s(object_data, "text", "hello world".into());
+    let anchor = anchor_from_name(&get_property_value_from_object_or_default_s(
+        object_data,
+        "anchor",
+        "Center".into(),
+    ));
 
     let x_offset = get_property_value_from_object_or_default_f(object_data, "x_offset", 0.0);
     let y_offset = get_property_value_from_object_or_default_f(object_data, "y_offset", 0.0);
 
+    let location = ui_location_from_anchor_offsets(
+        anchor,
+        Vec2::new(RES_WIDTH as f32, RES_HEIGHT as f32),
+        Vec2::new(x_offset as f32, y_offset as f32),
+    )
+    .truncate();
+    let size = Vec2::new(text.len() as f32 * BUTTON_CHAR_WIDTH, BUTTON_HEIGHT);
+
     entity_commands
-        .insert((NodeBundle {
-            style: Style {
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
+        .insert((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: NORMAL_BUTTON,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(location.extend(BUTTON_Z)),
                 ..Default::default()
             },
-            ..Default::default()
-        },))
+            ClickableArea {
+                location,
+                shape: CollisionShape::Aabb {
+                    half_extents: size / 2.0,
+                },
+                name: object_data.name.clone(),
+                enabled: true,
+                z: BUTTON_Z,
+                priority: 0,
+            },
+            MenuButton,
+            button,
+            HIGH_RES_LAYERS,
+        ))
         .with_children(|parent| {
-            parent
-                .spawn((
-                    ButtonBundle {
-                        style: Style {
-                            width: Val::Px(text.len() as f32 * 33.0),
-                            height: Val::Px(60.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            left: Val::Px(x_offset as f32),
-                            top: Val::Px(y_offset as f32),
-                            position_type: PositionType::Relative,
-                            ..Default::default()
-                        },
-                        background_color: NORMAL_BUTTON.into(),
-                        ..default()
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(text, text_style),
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, 0.1),
+                        scale: Vec3::new(0.25, 0.25, 0.25),
+                        ..Default::default()
                     },
-                    StartButton,
-                    HIGH_RES_LAYERS,
-                ))
-                .with_children(|parent| {
-                    parent.spawn((TextBundle::from_section(text, text_style), HIGH_RES_LAYERS));
-                });
+                    ..Default::default()
+                },
+                HIGH_RES_LAYERS,
+            ));
         });
 }
 
@@ -168,8 +190,7 @@ fn add_hydrators(mut hydrators: ResMut<ComponentHydrators>) {
         .register_tag::<(LivesLabel, Uninintialized)>("LivesLabel")
         .register_tag::<(TreasuresLabel, Uninintialized)>("TreasuresLabel")
         .register_tag::<(TitleLabel, Uninintialized)>("TitleLabel")
-        .register_hydrator("Text2dBundle", hydrate_label)
-        .register_hydrator("StartButton", hydrate_start_button);
+        .register_hydrator("Text2dBundle", hydrate_label);
 }
 
 fn initialize_background_loop(
@@ -202,36 +223,35 @@ fn initialize_labels(
     }
 }
 
-fn start_button_system(
-    mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<StartButton>),
-    >,
-    audio_server: Res<AudioServer>,
-    mut next_state: ResMut<NextState<SceneState>>,
-    mut map_server: ResMut<MapServer>,
-    mut commands: Commands,
+fn update_button_colors(
+    hovered: Res<HoveredArea>,
+    focus: Res<ClickableFocus>,
+    mut button_q: Query<(Entity, &mut Sprite), With<MenuButton>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
-        match *interaction {
-            Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
-                commands.spawn(audio_server.click.create_one_shot());
-                map_server.map_idx = 1;
-                next_state.set(SceneState::Transitioning);
-            }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
-            }
-            Interaction::None => {
-                *color = NORMAL_BUTTON.into();
-            }
+    for (entity, mut sprite) in button_q.iter_mut() {
+        let color = if hovered.entity == Some(entity) && hovered.pressed {
+            PRESSED_BUTTON
+        } else if hovered.previewed(&focus) == Some(entity) {
+            HOVERED_BUTTON
+        } else {
+            NORMAL_BUTTON
+        };
+        if sprite.color != color {
+            sprite.color = color;
         }
     }
 }
 
 // Helpers
 
+fn anchor_from_name(name: &str) -> Anchor {
+    return match name {
+        "TopLeft" => Anchor::TopLeft,
+        "TopRight" => Anchor::TopRight,
+        _ => Anchor::Center,
+    };
+}
+
 fn ui_location_from_anchor_offsets(anchor: Anchor, window_vec: Vec2, offset_vec: Vec2) -> Vec3 {
     if anchor == Anchor::Center {
         return Vec3::new(offset_vec.x, offset_vec.y, 0.0);